@@ -0,0 +1,64 @@
+use crate::{
+    value::{Globals, Value},
+    Error, Result,
+};
+use std::{collections::HashMap, fs, path::Path};
+
+/// Pre-made answers to template variables
+pub type Answers = Globals;
+
+/// Parse a single `--var name=value` assignment
+pub fn assignment(input: &str) -> Result<(String, String)> {
+    input
+        .split_once('=')
+        .filter(|(name, _)| !name.trim().is_empty())
+        .map(|(name, value)| (name.trim().to_owned(), value.to_owned()))
+        .ok_or(Error::InvalidVariableAssignment(input.to_owned()))
+}
+
+/// Read answers from a toml or json file, decided by its extension
+pub fn load(path: &Path) -> Result<Answers> {
+    let text = fs::read_to_string(path).map_err(|_| Error::CantReadAnswers(path.to_path_buf()))?;
+
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => serde_json::from_str::<HashMap<String, serde_json::Value>>(&text)
+            .map_err(|e| Error::InvalidAnswers(e.to_string()))?
+            .into_iter()
+            .map(|(k, v)| match v {
+                serde_json::Value::String(s) => Ok((k, s.into())),
+                serde_json::Value::Number(n) => Ok((k, n.to_string().into())),
+                serde_json::Value::Bool(b) => Ok((k, b.to_string().into())),
+                // Choices of multiselect
+                serde_json::Value::Array(a) => a
+                    .iter()
+                    .map(|v| v.as_str().map(str::to_owned))
+                    .collect::<Option<Vec<_>>>()
+                    .map(|a| (k.clone(), Value::List(a)))
+                    .ok_or(Error::InvalidAnswers(format!("unsupported value for {k}"))),
+                _ => Err(Error::InvalidAnswers(format!("unsupported value for {k}"))),
+            })
+            .collect(),
+        Some("toml") => toml::from_str::<toml::Table>(&text)
+            .map_err(|e| Error::InvalidAnswers(e.to_string()))?
+            .into_iter()
+            .map(|(k, v)| match v {
+                toml::Value::String(s) => Ok((k, s.into())),
+                toml::Value::Integer(i) => Ok((k, i.to_string().into())),
+                toml::Value::Float(f) => Ok((k, f.to_string().into())),
+                toml::Value::Boolean(b) => Ok((k, b.to_string().into())),
+                // Choices of multiselect
+                toml::Value::Array(a) => a
+                    .iter()
+                    .map(|v| v.as_str().map(str::to_owned))
+                    .collect::<Option<Vec<_>>>()
+                    .map(|a| (k.clone(), Value::List(a)))
+                    .ok_or(Error::InvalidAnswers(format!("unsupported value for {k}"))),
+                _ => Err(Error::InvalidAnswers(format!("unsupported value for {k}"))),
+            })
+            .collect(),
+        _ => Err(Error::InvalidAnswers(format!(
+            "unknown answers file format: {}",
+            path.display()
+        ))),
+    }
+}
@@ -0,0 +1,239 @@
+use crate::{config::CacheConfig, method::local::copy_tree, Error, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use walkdir::WalkDir;
+
+/// Directory with fetched contents inside of each entry
+static SOURCE: &str = "source";
+
+/// Metadata file inside of each entry
+static META: &str = "meta.toml";
+
+/// Prefix of entries which are still being written
+static STAGING: &str = ".staging-";
+
+/// What is known about cached source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Meta {
+    /// Url of source, without credentials
+    pub source: String,
+
+    /// Fetching method used
+    pub method: String,
+
+    /// Branch, tag or commit asked for
+    pub reference: Option<String>,
+
+    /// Revision it was resolved to
+    pub revision: Option<String>,
+
+    /// Seconds since unix epoch
+    pub fetched: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub meta: Meta,
+}
+
+impl Entry {
+    /// Copy cached contents to given directory
+    pub fn restore(&self, destination: &Path) -> Result<()> {
+        copy_tree(&self.path.join(SOURCE), destination)
+    }
+
+    /// How long ago it was fetched
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.meta.fetched))
+    }
+
+    /// Whether it was asked for exact commit, which can't move
+    pub fn pinned(&self) -> bool {
+        self.meta.reference.as_deref().is_some_and(|r| {
+            r.len() == 40
+                && r.chars().all(|c| c.is_ascii_hexdigit())
+                && self.meta.revision.as_deref() == Some(r)
+        })
+    }
+
+    /// Size of contents in bytes
+    pub fn size(&self) -> u64 {
+        WalkDir::new(&self.path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter_map(|e| e.metadata().ok())
+            .filter(|m| m.is_file())
+            .map(|m| m.len())
+            .sum()
+    }
+}
+
+/// Fetched templates living under $XDG_CACHE_HOME/bleur
+#[derive(Debug, Clone)]
+pub struct Cache {
+    root: PathBuf,
+
+    /// Age after which moving sources are fetched again
+    ttl: Duration,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl Cache {
+    pub fn open() -> Result<Self> {
+        dirs::cache_dir()
+            .map(|d| Self {
+                root: d.join("bleur"),
+                ttl: Duration::from_secs(CacheConfig::default().ttl * 3600),
+            })
+            .ok_or(Error::NoCacheDirectory)
+    }
+
+    pub fn with_settings(self, settings: CacheConfig) -> Self {
+        Self {
+            ttl: Duration::from_secs(settings.ttl.saturating_mul(3600)),
+            ..self
+        }
+    }
+
+    /// Whether entry can be used without fetching source again
+    pub fn fresh(&self, entry: &Entry) -> bool {
+        entry.pinned() || entry.age() <= self.ttl
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Same source, method and reference always land in same place
+    fn key(source: &str, method: &str, reference: Option<&str>) -> String {
+        let mut hasher = Sha256::new();
+
+        hasher.update(method.as_bytes());
+        hasher.update([0]);
+        hasher.update(source.as_bytes());
+        hasher.update([0]);
+        hasher.update(reference.unwrap_or_default().as_bytes());
+
+        format!("{:x}", hasher.finalize())
+    }
+
+    fn read(path: &Path) -> Option<Entry> {
+        fs::read_to_string(path.join(META))
+            .ok()
+            .and_then(|text| toml::from_str(&text).ok())
+            .filter(|_| path.join(SOURCE).is_dir())
+            .map(|meta| Entry {
+                path: path.to_path_buf(),
+                meta,
+            })
+    }
+
+    pub fn lookup(&self, source: &str, method: &str, reference: Option<&str>) -> Option<Entry> {
+        Self::read(&self.root.join(Self::key(source, method, reference)))
+    }
+
+    /// Save freshly fetched contents, replacing older copy. Entry is
+    /// put together aside and renamed into place, so concurrent runs
+    /// see either the old copy, the new one or none, never a half.
+    pub fn store(&self, meta: Meta, contents: &Path) -> Result<Entry> {
+        let path = self.root.join(Self::key(
+            &meta.source,
+            &meta.method,
+            meta.reference.as_deref(),
+        ));
+
+        fs::create_dir_all(&self.root)?;
+
+        let staging = self.staging()?;
+        fs::create_dir_all(staging.path().join(SOURCE))?;
+        copy_tree(contents, &staging.path().join(SOURCE))?;
+        fs::write(
+            staging.path().join(META),
+            toml::to_string(&meta).map_err(|e| Error::InvalidCache(e.to_string()))?,
+        )?;
+
+        // Directories can't be renamed over each other, so old copy moves aside first
+        if path.exists() {
+            let old = self.staging()?;
+            fs::rename(&path, old.path().join(SOURCE))?;
+        }
+
+        // Someone else has just stored the same thing, theirs is as good
+        if fs::rename(staging.path(), &path).is_err() && Self::read(&path).is_none() {
+            return Err(Error::InvalidCache(path.to_string_lossy().to_string()));
+        }
+
+        Ok(Entry { path, meta })
+    }
+
+    /// Scratch directory next to entries, removed once dropped
+    fn staging(&self) -> Result<tempfile::TempDir> {
+        tempfile::Builder::new()
+            .prefix(STAGING)
+            .tempdir_in(&self.root)
+            .map_err(Error::IOError)
+    }
+
+    pub fn list(&self) -> Result<Vec<Entry>> {
+        if !self.root.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = fs::read_dir(&self.root)?
+            .filter_map(|e| e.ok())
+            .filter(|e| !e.file_name().to_string_lossy().starts_with(STAGING))
+            .filter_map(|e| Self::read(&e.path()))
+            .collect::<Vec<Entry>>();
+
+        entries.sort_by_key(|e| std::cmp::Reverse(e.meta.fetched));
+
+        Ok(entries)
+    }
+
+    /// Remove everything, returning how many entries were there
+    pub fn clean(&self) -> Result<usize> {
+        let count = self.list()?.len();
+
+        if self.root.exists() {
+            fs::remove_dir_all(&self.root)?;
+        }
+
+        Ok(count)
+    }
+
+    /// Remove entries fetched earlier than given age
+    pub fn prune(&self, older: Duration) -> Result<usize> {
+        self.list()?
+            .into_iter()
+            .filter(|e| e.age() > older)
+            .try_fold(0, |count, e| fs::remove_dir_all(&e.path).map(|_| count + 1))
+            .map_err(Error::IOError)
+    }
+
+    pub fn meta(
+        source: String,
+        method: String,
+        reference: Option<String>,
+        revision: Option<String>,
+    ) -> Meta {
+        Meta {
+            source,
+            method,
+            reference,
+            revision,
+            fetched: now(),
+        }
+    }
+}
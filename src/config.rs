@@ -0,0 +1,222 @@
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, env, fs, path::PathBuf};
+
+/// Environment variable to point at another user configuration
+pub static CONFIG_ENV: &str = "BLEUR_CONFIG";
+
+/// Per-user settings, living at $XDG_CONFIG_HOME/bleur/config.toml
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct UserConfig {
+    /// Keys which are allowed to sign template archives
+    #[serde(default)]
+    pub trusted: TrustedKeys,
+
+    /// Sha256 of archive per source url, same as passing --sha256
+    #[serde(default)]
+    pub pins: HashMap<String, String>,
+
+    /// Knobs of http method
+    #[serde(default)]
+    pub http: HttpConfig,
+
+    /// Knobs of git method
+    #[serde(default)]
+    pub git: GitConfig,
+
+    /// Credentials for private sources
+    #[serde(default)]
+    pub auth: AuthConfig,
+
+    /// Facts exposed to templates
+    #[serde(default)]
+    pub context: ContextConfig,
+
+    /// Knobs of fetched templates cache
+    #[serde(default)]
+    pub cache: CacheConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Hours after which cached branches and archives are fetched
+    /// again, sources pinned to a commit never get stale
+    pub ttl: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self { ttl: 24 }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ContextConfig {
+    /// Environment variables templates may read as `@env.NAME@`
+    #[serde(default)]
+    pub env: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct AuthConfig {
+    /// Private key for ssh remotes, tried after ssh-agent
+    pub ssh_key: Option<PathBuf>,
+
+    /// Credentials per host, like `[auth.hosts."github.com"]`
+    #[serde(default)]
+    pub hosts: HashMap<String, HostAuth>,
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct HostAuth {
+    /// User name for basic auth or git over https
+    pub username: Option<String>,
+
+    /// Password for basic auth
+    pub password: Option<String>,
+
+    /// Token sent as bearer or git password
+    pub token: Option<String>,
+
+    /// Name of environment variable holding the token
+    pub token_env: Option<String>,
+}
+
+// Secrets must never end up in logs or errors
+impl std::fmt::Debug for HostAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HostAuth")
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .field("token_env", &self.token_env)
+            .finish()
+    }
+}
+
+/// Resolved credential for a single host
+#[derive(Clone)]
+pub enum Credential {
+    Basic(String, String),
+    Bearer(String),
+}
+
+impl std::fmt::Debug for Credential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Basic(user, _) => write!(f, "Basic({user}, <redacted>)"),
+            Self::Bearer(_) => write!(f, "Bearer(<redacted>)"),
+        }
+    }
+}
+
+impl Credential {
+    /// User name and password pair for git over https
+    pub fn userpass(&self, host: &str) -> (String, String) {
+        match self {
+            Self::Basic(user, password) => (user.to_owned(), password.to_owned()),
+            // GitLab wants exactly this name, others take any
+            Self::Bearer(token) if host.contains("gitlab") => ("oauth2".into(), token.to_owned()),
+            Self::Bearer(token) => ("x-access-token".into(), token.to_owned()),
+        }
+    }
+}
+
+impl AuthConfig {
+    /// Look up configured credentials of host, then well known
+    /// environment variables like GITHUB_TOKEN
+    pub fn credential(&self, host: &str) -> Option<Credential> {
+        let configured = self.hosts.get(host).and_then(|h| {
+            match (&h.username, &h.password, &h.token, &h.token_env) {
+                (Some(user), Some(password), _, _) => {
+                    Some(Credential::Basic(user.to_owned(), password.to_owned()))
+                }
+                (user, _, token, variable) => token
+                    .clone()
+                    .or_else(|| variable.as_ref().and_then(|v| env::var(v).ok()))
+                    .map(|t| match user {
+                        Some(user) => Credential::Basic(user.to_owned(), t),
+                        None => Credential::Bearer(t),
+                    }),
+            }
+        });
+
+        let variable = match host {
+            "github.com" | "api.github.com" | "codeload.github.com" => Some("GITHUB_TOKEN"),
+            "gitlab.com" => Some("GITLAB_TOKEN"),
+            _ => None,
+        };
+
+        configured.or_else(|| {
+            variable
+                .and_then(|v| env::var(v).ok())
+                .map(Credential::Bearer)
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct GitConfig {
+    /// Own shorthands like `work = "https://git.example.corp/{owner}/{repo}"`
+    #[serde(default)]
+    pub providers: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TrustedKeys {
+    /// Base64 encoded minisign public keys
+    #[serde(default)]
+    pub minisign: Vec<String>,
+
+    /// OpenSSH formatted public keys
+    #[serde(default)]
+    pub ssh: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    /// Largest archive allowed to download, in megabytes
+    pub max_size: u64,
+
+    /// Seconds to wait for connection to be established
+    pub connect_timeout: u64,
+
+    /// Seconds to wait for each read or write
+    pub timeout: u64,
+
+    /// How many more times to try when server or network fails
+    pub retries: u32,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            max_size: 200,
+            connect_timeout: 10,
+            timeout: 30,
+            retries: 3,
+        }
+    }
+}
+
+impl UserConfig {
+    pub fn path() -> Option<PathBuf> {
+        env::var_os(CONFIG_ENV)
+            .map(PathBuf::from)
+            .or_else(|| dirs::config_dir().map(|d| d.join("bleur").join("config.toml")))
+    }
+
+    /// Missing configuration is fine, broken one is not
+    pub fn load() -> Result<Self> {
+        match Self::path().filter(|p| p.exists()) {
+            Some(path) => fs::read_to_string(&path)
+                .map_err(Error::IOError)
+                .and_then(|text| {
+                    toml::from_str(&text).map_err(|e| Error::InvalidUserConfig(e.to_string()))
+                }),
+            None => Ok(Self::default()),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::{
+    collections::HashMap,
+    env,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Facts about environment every template can refer to,
+/// like `@bleur.year@` or `@git.user.email@`
+pub fn gather(destination: &Path, allowed_env: &[String]) -> HashMap<String, String> {
+    let mut context = HashMap::new();
+
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86400)
+        .unwrap_or_default();
+    let (year, month, day) = civil(days as i64);
+
+    context.insert("bleur.year".into(), year.to_string());
+    context.insert(
+        "bleur.date".into(),
+        format!("{year:04}-{month:02}-{day:02}"),
+    );
+    context.insert("bleur.os".into(), env::consts::OS.into());
+
+    // Relative paths like "." have no name of their own
+    if let Some(name) = std::path::absolute(destination)
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+    {
+        context.insert("bleur.dir_name".into(), name);
+    }
+
+    if let Ok(config) = git2::Config::open_default() {
+        for key in ["user.name", "user.email"] {
+            if let Ok(value) = config.get_string(key) {
+                context.insert(format!("git.{key}"), value);
+            }
+        }
+    }
+
+    // Environment may hold secrets, so only what user allowed
+    for name in allowed_env {
+        if let Ok(value) = env::var(name) {
+            context.insert(format!("env.{name}"), value);
+        }
+    }
+
+    context
+}
+
+/// Year, month and day of days since unix epoch
+fn civil(days: i64) -> (i64, u32, u32) {
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
@@ -19,20 +19,58 @@ pub enum BleurError {
     CantCloneRepository(#[from] git2::Error),
     #[error("can't download from given url via http: {0}")]
     CantDownloadViaHttp(#[from] reqwest::Error),
-    #[error("you don't have nix nor git for initialization")]
+    #[error("you don't have nix installed for fetching flake templates")]
     NoToolForInit,
     #[error("we don't have enough of arguments to decide which fetching scheme to use")]
     InsufficientArgumentsToDecide,
     #[error("failed while executing a command")]
     CommandExecutionFail,
+    #[error("flake doesn't provide such template: {0}")]
+    NoSuchFlakeTemplate(String),
     #[error("can't get length of content via http")]
     CantGetContentLength,
+    #[error("archive is larger than allowed {0} megabytes")]
+    ArchiveTooLarge(u64),
+    #[error("connection broke while downloading: {0}")]
+    CantReadResponse(String),
     #[error("can't create file to write downloads {0}")]
     CantCreateFile(String),
     #[error("can't write to file after downloading")]
     CantWriteToFile,
     #[error("can't unzip downloaded zip file: {0}")]
     CantUnArchiveZip(#[from] zip::result::ZipError),
+    #[error("can't untar downloaded tarball: {0}")]
+    CantUnArchiveTar(std::io::Error),
+    #[error("can't open downloaded archive: {0}")]
+    CantOpenArchive(std::io::Error),
+    #[error("can't create directory while extracting archive: {0}")]
+    CantCreateDirectory(PathBuf),
+    #[error("can't extract archive entry to {0}: {1}")]
+    CantExtractEntry(PathBuf, std::io::Error),
+    #[error("can't set permissions of extracted file: {0}")]
+    CantSetPermissions(PathBuf),
+    #[error("archive entry tries to escape destination: {0}")]
+    UnsafeArchiveEntry(String),
+    #[error("archive link {0} points outside of destination: {1}")]
+    UnsafeArchiveLink(String, String),
+    #[error("can't figure out archive format of downloaded file: {0}")]
+    UnknownArchiveFormat(String),
+    #[error("given sha256 checksum is not valid hex: {0}")]
+    InvalidChecksum(String),
+    #[error("checksum mismatch, expected {0} but got {1}")]
+    ChecksumMismatch(String, String),
+    #[error("can't read signature of archive: {0}")]
+    CantReadSignature(String),
+    #[error("signature of archive is malformed: {0}")]
+    InvalidSignature(String),
+    #[error("there are no trusted keys of this signature kind in user config")]
+    NoTrustedKeys,
+    #[error("archive is not signed by any of trusted keys")]
+    SignatureNotTrusted,
+    #[error("checksum and signature verification is only possible with http method")]
+    VerificationNeedsArchive,
+    #[error("can't parse user configuration: {0}")]
+    InvalidUserConfig(String),
     #[error("can't delete downloaded archive from archived directory")]
     CantDeleteOldArchive,
     #[error(
@@ -51,20 +89,68 @@ pub enum BleurError {
     InvalidFilePath(PathBuf),
     #[error("invalid regular expression for captchuring variable names: {0}")]
     InvalidRegex(#[from] regex::Error),
-    #[error("there's no such variable in bleur.toml: {0}")]
+    #[error("unknown or malformed apply function: {0}")]
+    UnknownApplyFunction(String),
+    #[error("invalid glob pattern in bleur.toml: {0}")]
+    InvalidGlob(String),
+    #[error("change source matches no files, mark it optional if that's fine: {0}")]
+    NoMatchingFiles(String),
+    #[error("can't render template {0}: {1}")]
+    CantRenderTemplate(PathBuf, String),
+    #[error("invalid condition {0:?}: {1}")]
+    InvalidCondition(String, String),
+    #[error("there's no such variable in bleur.toml (or it's declared later): {0}")]
     NoSuchVariable(String),
+    #[error("variables of bleur.toml refer to each other in a cycle: {0}")]
+    VariableCycle(String),
+    #[error("{0}: {1}")]
+    InTask(String, Box<BleurError>),
+    #[error("file already exists, bleur won't overwrite it: {0}")]
+    FileAlreadyExists(PathBuf),
+    #[error("can't copy a directory into itself: {0}")]
+    CopyIntoItself(PathBuf),
     #[error("can't move/rename given file: {0}")]
     CantMoveFile(String),
-    #[error("the given git provider is known: {0}")]
+    #[error("the given git provider is unknown: {0}")]
     UnknownGitProvider(String),
     #[error("the given git repository owner in the url is invalid: {0}")]
     InvalidRepositoryOwner(String),
     #[error("the given git repository name in the url is invalid: {0}")]
     InvalidRepositoryName(String),
+    #[error("can't find branch, tag or commit in repository: {0}")]
+    NoSuchRevision(String),
+    #[error("pinning a revision is only possible with git method")]
+    ReferenceNeedsGit,
+    #[error("there's no such directory inside of fetched source: {0}")]
+    NoSuchSubdirectory(PathBuf),
+    #[error("can't figure out where cache directory should be")]
+    NoCacheDirectory,
+    #[error("can't write cache metadata: {0}")]
+    InvalidCache(String),
+    #[error("source isn't cached yet, can't fetch it while offline: {0}")]
+    NotCached(String),
     #[error("git error: {0}")]
     GitError(git2::Error),
     #[error("brotha, what on earth makes you want collection more than {0} depths?")]
     AintNoWayThisDeepCollection(u8),
+    #[error("invalid variable assignment, expected name=value: {0}")]
+    InvalidVariableAssignment(String),
+    #[error("can't read answers file: {0}")]
+    CantReadAnswers(PathBuf),
+    #[error("can't parse answers file: {0}")]
+    InvalidAnswers(String),
+    #[error("no value was given for variable {0} and it has no default")]
+    MissingVariable(String),
+    #[error("value {1:?} for variable {0} is invalid: {2}")]
+    InvalidVariableValue(String, String, String),
+    #[error("variable {0} is defined incorrectly in bleur.toml: {1}")]
+    InvalidVariableDefinition(String, String),
+    #[error(
+        "{0} file(s) already exist at destination, choose what to do with them via --on-conflict"
+    )]
+    DestinationConflicts(usize),
+    #[error("collection requires choosing a template, pass one of: {0}")]
+    TemplateNameRequired(String),
 
     // To be used only if you get despaired.
     // Until so, don't touch, for the sake of your own sanity!
@@ -1,24 +1,152 @@
 pub mod task;
 
-use std::collections::HashMap;
+use std::{collections::HashMap, path::PathBuf};
 
-use crate::{execute::task::Task, schemes::template::Template, Result};
+use owo_colors::OwoColorize;
+
+use crate::{
+    execute::task::Task,
+    manager::Glubtastic,
+    schemes::template::Template,
+    value::{self, Globals},
+    Error, Result,
+};
 
 #[derive(Debug)]
 pub struct Executor {
     tasks: Vec<Task>,
+    root: PathBuf,
+    interactive: bool,
+    dry_run: bool,
 }
 
 impl Executor {
     pub fn consume(template: Template) -> Self {
         Self {
+            root: template.path().to_owned(),
             tasks: template.to_tasks(),
+            interactive: true,
+            dry_run: false,
         }
     }
 
-    pub fn compute(self, global: &mut HashMap<String, String>) -> Result<()> {
-        self.tasks
+    /// Whether variables can be prompted from user
+    pub fn interactive(self, interactive: bool) -> Self {
+        Self {
+            interactive,
+            ..self
+        }
+    }
+
+    /// Print plan of each task while computing
+    pub fn dry_run(self, dry_run: bool) -> Self {
+        Self { dry_run, ..self }
+    }
+
+    pub fn compute(self, global: &mut Globals) -> Result<()> {
+        self.cycles(global)?;
+
+        self.tasks.iter().try_for_each(|t| {
+            t.validate()
+                .map_err(|e| Error::InTask(t.name(), Box::new(e)))
+        })?;
+
+        self.tasks.iter().try_for_each(|t| {
+            // Point at the task which refers to unknown variable or filter
+            self.perform(t, global).map_err(|e| match e {
+                Error::NoSuchVariable(_) | Error::UnknownApplyFunction(_) => {
+                    Error::InTask(t.name(), Box::new(e))
+                }
+                e => e,
+            })
+        })
+    }
+
+    fn perform(&self, t: &Task, global: &mut Globals) -> Result<()> {
+        match t {
+            // Condition is checked right before, as earlier
+            // tasks might have changed globals it relies on
+            _ if !t.applies(global)? => {
+                if self.dry_run {
+                    println!("{} {}", "skip".dimmed(), t.name());
+                }
+
+                Ok(())
+            }
+            // Variable values are known only after asking
+            Task::Variable(_) => {
+                t.execute(global, self.interactive)?;
+                self.report(t, global)
+            }
+            _ => {
+                self.report(t, global)?;
+                t.execute(global, self.interactive)
+            }
+        }
+    }
+
+    /// Refuse variables which depend on each other, as none
+    /// of them could ever be resolved
+    fn cycles(&self, global: &Globals) -> Result<()> {
+        let dependencies: HashMap<&str, Vec<String>> = self
+            .tasks
             .iter()
-            .try_for_each(|t| t.to_owned().execute(global))
+            .filter_map(|t| match t {
+                // Values given upfront are never expanded, so they can't loop
+                Task::Variable(v) if global.contains_key(v.name()) => None,
+                Task::Variable(v) => Some((
+                    v.name(),
+                    v.references().flat_map(|r| global.globs(r)).collect(),
+                )),
+                _ => None,
+            })
+            .collect();
+
+        fn visit<'a>(
+            name: &'a str,
+            dependencies: &'a HashMap<&str, Vec<String>>,
+            path: &mut Vec<&'a str>,
+        ) -> Option<String> {
+            if let Some(start) = path.iter().position(|p| *p == name) {
+                let mut cycle = path[start..].to_vec();
+                cycle.push(name);
+                return Some(cycle.join(" -> "));
+            }
+
+            path.push(name);
+            let cycle = dependencies
+                .get(name)
+                .into_iter()
+                .flatten()
+                .find_map(|d| visit(d, dependencies, path));
+            path.pop();
+
+            cycle
+        }
+
+        let mut names = dependencies.keys().collect::<Vec<_>>();
+        names.sort();
+
+        match names
+            .into_iter()
+            .find_map(|n| visit(n, &dependencies, &mut Vec::new()))
+        {
+            Some(cycle) => Err(Error::VariableCycle(cycle)),
+            None => Ok(()),
+        }
+    }
+
+    fn report(&self, task: &Task, global: &Globals) -> Result<()> {
+        if self.dry_run {
+            // Secrets must not end up in diffs on screen
+            let plan = task.plan(&value::redacted(global), &self.root)?;
+
+            // Some tasks might have nothing to do at all
+            if !plan.is_empty() {
+                println!("{plan}");
+            }
+        }
+
+        Ok(())
     }
 }
@@ -1,6 +1,12 @@
-use crate::schemes::template::prelude::{change::Change, r#move::Move, variable::Variable};
-use crate::Result;
-use std::collections::HashMap;
+use crate::schemes::template::{
+    apply::Apply,
+    condition::{self, Condition},
+    prelude::{
+        append::Append, change::Change, copy::Copy, create::Create, delete::Delete, r#move::Move,
+        rename::Rename, render::Render, substitute::Substitute, variable::Variable,
+    },
+};
+use crate::{value::Globals, Result};
 use std::path::Path;
 
 pub trait ToTask {
@@ -17,14 +23,116 @@ pub enum Task {
 
     /// Move a file from a place to place
     Move(Move),
+
+    /// Replace variables in every text file
+    Substitute(Substitute),
+
+    /// Render files with template engine
+    Render(Render),
+
+    /// Rename files and directories with variables in names
+    Rename(Rename),
+
+    /// Remove a file or directory
+    Delete(Delete),
+
+    /// Write a new file
+    Create(Create),
+
+    /// Add lines to the end or beginning of a file
+    Append(Append),
+
+    /// Copy a file or directory to another place
+    Copy(Copy),
 }
 
 impl Task {
-    pub fn execute(&self, global: &mut HashMap<String, String>) -> Result<()> {
+    pub fn execute(&self, global: &mut Globals, interactive: bool) -> Result<()> {
         match self {
-            Self::Variable(v) => v.execute(global),
+            Self::Variable(v) => v.execute(global, interactive),
             Self::Change(c) => c.execute(global),
             Self::Move(m) => m.execute(global),
+            Self::Substitute(s) => s.execute(global),
+            Self::Render(r) => r.execute(global),
+            Self::Rename(r) => r.execute(global),
+            Self::Delete(d) => d.execute(global),
+            Self::Create(c) => c.execute(global),
+            Self::Append(a) => a.execute(global),
+            Self::Copy(c) => c.execute(global),
+        }
+    }
+
+    /// Whether `when` condition of task holds
+    pub fn applies(&self, global: &Globals) -> Result<bool> {
+        condition::applies(
+            match self {
+                Self::Variable(v) => v.when(),
+                Self::Change(c) => c.when(),
+                Self::Move(m) => m.when(),
+                Self::Delete(d) => d.when(),
+                Self::Create(c) => c.when(),
+                Self::Append(a) => a.when(),
+                Self::Copy(c) => c.when(),
+                Self::Substitute(_) | Self::Render(_) | Self::Rename(_) => None,
+            },
+            global,
+        )
+    }
+
+    /// Catch mistakes in functions and conditions before
+    /// anything gets asked or changed
+    pub fn validate(&self) -> Result<()> {
+        let (apply, when) = match self {
+            Self::Variable(v) => (v.apply(), v.when()),
+            Self::Change(c) => {
+                c.check()?;
+                (c.apply(), c.when())
+            }
+            Self::Move(m) => (m.apply(), m.when()),
+            Self::Delete(d) => ("", d.when()),
+            Self::Create(c) => (c.apply(), c.when()),
+            Self::Append(a) => (a.apply(), a.when()),
+            Self::Copy(c) => (c.apply(), c.when()),
+            Self::Substitute(_) | Self::Rename(_) => return Ok(()),
+            Self::Render(r) => return r.patterns().map(|_| ()),
+        };
+
+        Apply::parse(apply)?;
+        when.map(Condition::parse).transpose()?;
+
+        Ok(())
+    }
+
+    /// Short name of task, for when it's skipped
+    pub fn name(&self) -> String {
+        match self {
+            Self::Variable(v) => format!("variable {}", v.name()),
+            Self::Change(c) => format!("change {}", c.source()),
+            Self::Move(m) => format!("move {}", m.from().display()),
+            Self::Substitute(_) => "substitute".to_owned(),
+            Self::Render(_) => "render".to_owned(),
+            Self::Rename(_) => "rename".to_owned(),
+            Self::Delete(d) => format!("delete {}", d.path()),
+            Self::Create(c) => format!("create {}", c.path()),
+            Self::Append(a) => format!("{} {}", a.label(), a.path()),
+            Self::Copy(c) => format!("copy {}", c.from()),
+        }
+    }
+
+    /// Human readable description of what task does. Variables
+    /// are described after execution, others right before it.
+    pub fn plan(&self, global: &Globals, root: &Path) -> Result<String> {
+        match self {
+            Self::Variable(v) => v.plan(global),
+            Self::Change(c) => c.plan(global, root),
+            Self::Move(m) => m.plan(global, root),
+            Self::Substitute(s) => s.plan(global, root),
+            Self::Render(r) => r.plan(global, root),
+            Self::Rename(r) => r.plan(global, root),
+            Self::Delete(d) => d.plan(global, root),
+            Self::Create(c) => c.plan(global, root),
+            Self::Append(a) => a.plan(global, root),
+            Self::Copy(c) => c.plan(global, root),
         }
     }
 
@@ -34,11 +142,32 @@ impl Task {
             // First get the all variables
             Self::Variable(_) => 1,
 
+            // Then clean up what template doesn't need
+            Self::Delete(_) => 2,
+
+            // Then duplicate files and directories
+            Self::Copy(_) => 3,
+
+            // Then write new files
+            Self::Create(_) => 4,
+
+            // Then add lines to existing or fresh files
+            Self::Append(_) => 5,
+
             // Then proceed with moving folders from->to desitinations
-            Self::Change(_) => 2,
+            Self::Change(_) => 6,
+
+            // Then replace variables all over the tree
+            Self::Substitute(_) => 7,
+
+            // Then render files with template engine
+            Self::Render(_) => 8,
 
             // Then replace contents inside files
-            Self::Move(_) => 3,
+            Self::Move(_) => 9,
+
+            // Finally rename what's left with variables in names
+            Self::Rename(_) => 10,
         }
     }
 }
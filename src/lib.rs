@@ -1,13 +1,18 @@
+pub mod answers;
+pub mod cache;
+pub mod config;
+pub mod context;
 pub mod error;
 pub mod execute;
 pub mod manager;
 pub mod method;
 pub mod schemes;
+pub mod value;
 
 use crate::method::Methodical;
 use clap::{Parser, Subcommand, ValueEnum};
 pub use error::{beautiful_exit, BleurError as Error, Result};
-use method::{git::Git, http::Http, Method};
+use method::{git::Git, http::Http, local::Local, nix::Nix, Method};
 use std::path::PathBuf;
 use url::Url;
 
@@ -18,6 +23,8 @@ pub static COLLECTION: &str = include_str!("./template/collection.toml");
 pub enum Protocol {
     Git,
     Http,
+    Local,
+    Nix,
 }
 
 impl std::fmt::Display for Protocol {
@@ -25,6 +32,8 @@ impl std::fmt::Display for Protocol {
         match self {
             Self::Git => write!(f, "git"),
             Self::Http => write!(f, "http"),
+            Self::Local => write!(f, "local"),
+            Self::Nix => write!(f, "nix"),
         }
     }
 }
@@ -34,6 +43,36 @@ impl Methodical for Protocol {
         match self {
             Self::Git => Method::Git(Git::new(url, path)),
             Self::Http => Method::Http(Http::new(url, path)),
+            Self::Local => Method::Local(Local::new(url, path)),
+            Self::Nix => Method::Nix(Nix::new(url, path)),
+        }
+    }
+}
+
+/// What to do with files already existing at destination
+#[derive(ValueEnum, Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Conflict {
+    /// Stop before anything gets written
+    #[default]
+    Abort,
+    /// Keep existing files as they are
+    Skip,
+    /// Replace existing files
+    Overwrite,
+    /// Ask about each existing file
+    Prompt,
+    /// Rename existing files to *.bak before writing
+    Backup,
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Abort => write!(f, "abort"),
+            Self::Skip => write!(f, "skip"),
+            Self::Overwrite => write!(f, "overwrite"),
+            Self::Prompt => write!(f, "prompt"),
+            Self::Backup => write!(f, "backup"),
         }
     }
 }
@@ -50,6 +89,25 @@ pub struct Cli {
     pub command: Commands,
 }
 
+#[derive(Debug, Subcommand)]
+pub enum CacheAction {
+    /// Show cached templates
+    List,
+
+    /// Remove every cached template
+    Clean,
+
+    /// Remove cached templates fetched long ago
+    Prune {
+        /// Age in days after which template is removed
+        #[arg(long, value_name = "DAYS")]
+        #[clap(default_value_t = 30)]
+        older_than: u64,
+    },
+}
+
+// Parsed only once, no point in boxing arguments
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Subcommand)]
 pub enum Commands {
     /// Start creating new project
@@ -65,12 +123,97 @@ pub enum Commands {
         #[clap(default_value = "https://github.com/bleur-org/templates")]
         template: String,
 
-        /// Chosen method of fetching repository
+        /// Chosen method of fetching repository,
+        /// local paths and file:// urls are always local
         #[arg(short, long)]
         #[clap(default_value_t = Protocol::Git)]
         method: Protocol,
+
+        /// Assign value to a template variable
+        /// instead of asking for it
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = answers::assignment)]
+        vars: Vec<(String, String)>,
+
+        /// Toml or json file with values
+        /// for template variables
+        #[arg(long, value_name = "FILE")]
+        answers: Option<PathBuf>,
+
+        /// Name of template to pick from collection,
+        /// nested collections are separated by slash
+        #[arg(long, value_name = "KEY")]
+        template_name: Option<String>,
+
+        /// Never prompt, fall back to defaults
+        /// of variables instead
+        #[arg(long)]
+        no_input: bool,
+
+        /// Print what would be done without
+        /// touching the destination
+        #[arg(long)]
+        dry_run: bool,
+
+        /// What to do with files which
+        /// already exist at destination
+        #[arg(long, value_name = "POLICY")]
+        #[clap(default_value_t = Conflict::Abort)]
+        on_conflict: Conflict,
+
+        /// Expected sha256 of downloaded archive,
+        /// checked before it gets extracted
+        #[arg(long, value_name = "HEX")]
+        sha256: Option<String>,
+
+        /// Path or url to minisign or ssh signature
+        /// of archive, checked against trusted keys
+        #[arg(long, value_name = "SIGNATURE")]
+        signature: Option<String>,
+
+        /// Largest archive allowed to download
+        /// in megabytes [default: 200]
+        #[arg(long, value_name = "MB")]
+        max_size: Option<u64>,
+
+        /// Branch, tag or commit of repository,
+        /// same as ?ref= or #... in template url
+        #[arg(long = "ref", value_name = "REF")]
+        reference: Option<String>,
+
+        /// Directory inside of source where template
+        /// lives, same as source//path syntax
+        #[arg(long, value_name = "PATH")]
+        subdir: Option<PathBuf>,
+
+        /// Use only cached sources,
+        /// never touch the network
+        #[arg(long, conflicts_with = "refresh")]
+        offline: bool,
+
+        /// Fetch source again even
+        /// if it's cached already
+        #[arg(long)]
+        refresh: bool,
+
+        /// Expose environment variable to
+        /// template as @env.NAME@
+        #[arg(long = "env", value_name = "NAME")]
+        env: Vec<String>,
+    },
+
+    /// Manage cache of fetched templates
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
     },
 
     /// Bootstrap a bleur toml file for a new template
     Init,
+
+    /// Print sha256 of archive or digest of template
+    /// directory, as pinned by --sha256 or collections
+    Digest {
+        /// Archive file or template directory
+        path: PathBuf,
+    },
 }
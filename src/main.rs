@@ -1,8 +1,17 @@
 #![allow(unused_variables)]
 
-use bleur::*;
+use bleur::{
+    config::{HttpConfig, UserConfig},
+    method::verify::{self, Verification},
+    *,
+};
 use clap::Parser;
-use std::{env::current_dir, fs::File, io::Write};
+use std::{
+    env::current_dir,
+    fs::{self, File},
+    io::Write,
+    time::Duration,
+};
 
 fn main() -> Result<()> {
     run().or_else(|e| beautiful_exit(e.to_string()))
@@ -14,10 +23,67 @@ fn run() -> Result<()> {
             template,
             path,
             method,
+            vars,
+            answers,
+            template_name,
+            no_input,
+            dry_run,
+            on_conflict,
+            sha256,
+            signature,
+            max_size,
+            reference,
+            subdir,
+            offline,
+            refresh,
+            env,
         } => path
             .map_or_else(|| current_dir().map_err(Error::IOError), Ok)
             .and_then(|p| {
+                // Answers file goes first, so --var could override it
+                let mut values = answers
+                    .as_deref()
+                    .map(answers::load)
+                    .transpose()?
+                    .unwrap_or_default();
+                values.extend(vars.into_iter().map(|(k, v)| (k, v.into())));
+
+                Ok((p, values))
+            })
+            .and_then(|(p, values)| {
+                let config = UserConfig::load()?;
+                let verification = Verification {
+                    sha256: sha256.or_else(|| config.pins.get(&template).cloned()),
+                    signature,
+                    trusted: config.trusted,
+                };
+                let http = HttpConfig {
+                    max_size: max_size.unwrap_or(config.http.max_size),
+                    ..config.http
+                };
+
+                let allowed_env = [config.context.env, env].concat();
+
                 manager::ManageBuilder::new()
+                    .context(context::gather(&p, &allowed_env))
+                    .answers(values)
+                    .template_name(template_name)
+                    .interactive(!no_input)
+                    .dry_run(dry_run)
+                    .on_conflict(on_conflict)
+                    .verification(verification)
+                    .http(http)
+                    .git(config.git)
+                    .auth(config.auth)
+                    .reference(reference)
+                    .subdir(subdir)
+                    .cache(
+                        cache::Cache::open()
+                            .ok()
+                            .map(|c| c.with_settings(config.cache)),
+                    )
+                    .offline(offline)
+                    .refresh(refresh)
                     .source(template)
                     .and_then(|b| b.tempdir())
                     .and_then(|b| b.fetch_method(method))
@@ -28,6 +94,37 @@ fn run() -> Result<()> {
                     .and_then(|m| m.recursively_copy(p))
             })
             .map(|_| ()),
+        Commands::Cache { action } => cache::Cache::open().and_then(|cache| match action {
+            CacheAction::List => cache.list().map(|entries| {
+                entries.iter().for_each(|e| {
+                    println!(
+                        "{} {} {} ({} days ago, {} KiB)",
+                        e.meta.method,
+                        e.meta.source,
+                        e.meta
+                            .revision
+                            .as_deref()
+                            .or(e.meta.reference.as_deref())
+                            .unwrap_or("-"),
+                        e.age().as_secs() / 86400,
+                        e.size() / 1024
+                    )
+                })
+            }),
+            CacheAction::Clean => cache
+                .clean()
+                .map(|count| println!("removed {count} cached template(s)")),
+            CacheAction::Prune { older_than } => cache
+                .prune(Duration::from_secs(older_than * 86400))
+                .map(|count| println!("removed {count} cached template(s)")),
+        }),
+        Commands::Digest { path } => match path.is_dir() {
+            true => verify::tree_digest(&path),
+            false => fs::read(&path)
+                .map_err(Error::IOError)
+                .map(|bytes| verify::digest(&bytes)),
+        }
+        .map(|digest| println!("{digest}")),
         Commands::Init => current_dir()
             .map_err(Error::IOError)
             .and_then(|directory| {
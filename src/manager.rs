@@ -1,23 +1,59 @@
 use crate::{
-    method::{Fetchable, Method, Methodical},
-    schemes::Configuration,
-    Error, Result,
+    answers::Answers,
+    cache::Cache,
+    config::{AuthConfig, GitConfig, HttpConfig},
+    method::{local::link, verify::Verification, Fetchable, Method, Methodical},
+    schemes::{template::apply::Apply, Configuration},
+    value::Globals,
+    Conflict, Error, Protocol, Result,
 };
-use dircpy::CopyBuilder;
+use owo_colors::OwoColorize;
 use regex::{Regex, RegexBuilder};
-use std::path::Path;
+use std::path::{Component, Path};
 use std::{collections::HashMap, fs, path::PathBuf, sync::LazyLock};
 use tempfile::{tempdir, TempDir};
 use url::Url;
+use walkdir::WalkDir;
 
-pub static REGEX: LazyLock<Regex> =
-    LazyLock::new(|| RegexBuilder::new(r"@([a-zA-Z0-9-_]+)@").build().unwrap());
+/// Catches `@name@` along with optional filters like `@name|snake_case|prefix:x@`
+pub static REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    RegexBuilder::new(r"@([a-zA-Z0-9-_.]+)((?:\|[^@|]*)*)@")
+        .build()
+        .unwrap()
+});
 
-#[derive(Default, Debug)]
+static SCP: LazyLock<Regex> = LazyLock::new(|| {
+    RegexBuilder::new(r"^([a-zA-Z0-9._-]+@[a-zA-Z0-9.-]+):([^/].*)$")
+        .build()
+        .unwrap()
+});
+
+#[derive(Debug)]
 pub struct ManageBuilder {
     remote: Option<Url>,
     temporary: Option<TempDir>,
     method: Option<Method>,
+    answers: Answers,
+    context: HashMap<String, String>,
+    template_name: Option<String>,
+    interactive: bool,
+    dry_run: bool,
+    on_conflict: Conflict,
+    verification: Verification,
+    http: HttpConfig,
+    git: GitConfig,
+    reference: Option<String>,
+    subdir: Option<PathBuf>,
+    auth: AuthConfig,
+    cache: Option<Cache>,
+    offline: bool,
+    refresh: bool,
+}
+
+impl Default for ManageBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ManageBuilder {
@@ -26,25 +62,150 @@ impl ManageBuilder {
             remote: None,
             temporary: None,
             method: None,
+            answers: Answers::default(),
+            context: HashMap::default(),
+            template_name: None,
+            interactive: true,
+            dry_run: false,
+            on_conflict: Conflict::default(),
+            verification: Verification::default(),
+            http: HttpConfig::default(),
+            git: GitConfig::default(),
+            reference: None,
+            subdir: None,
+            auth: AuthConfig::default(),
+            cache: None,
+            offline: false,
+            refresh: false,
         }
     }
 
     pub fn tempdir(self) -> Result<Self> {
         tempdir().map_err(Error::IOError).map(|t| Self {
-            remote: self.remote,
             temporary: Some(t),
-            method: self.method,
+            ..self
         })
     }
 
     pub fn source<T: AsRef<str>>(self, url: T) -> Result<Self> {
-        Url::parse(url.as_ref())
-            .map_err(Error::UrlError)
-            .map(|l| Self {
-                temporary: self.temporary,
-                method: self.method,
-                remote: Some(l),
-            })
+        let (url, found) = split_subdir(url.as_ref());
+        let subdir = self.subdir.or(found);
+        let local = Path::new(&url);
+
+        // Existing paths on disk are turned into file:// urls
+        if local.exists() {
+            return fs::canonicalize(local)
+                .map_err(Error::IOError)
+                .and_then(|p| Url::from_directory_path(&p).map_err(|_| Error::InvalidFilePath(p)))
+                .map(|l| Self {
+                    remote: Some(l),
+                    subdir,
+                    ..self
+                });
+        }
+
+        // Scp-like git@host:owner/repo is an ssh url in disguise
+        let url = match SCP.captures(&url) {
+            Some(c) => format!("ssh://{}/{}", &c[1], &c[2]),
+            None => url,
+        };
+
+        Url::parse(&url).map_err(Error::UrlError).map(|l| Self {
+            remote: Some(l),
+            subdir,
+            ..self
+        })
+    }
+
+    /// Directory inside of source where template or collection
+    /// lives, wins over one given via `source//path` syntax
+    pub fn subdir(self, subdir: Option<PathBuf>) -> Self {
+        Self {
+            subdir: subdir.or(self.subdir),
+            ..self
+        }
+    }
+
+    /// Values of variables which shouldn't be asked
+    pub fn answers(self, answers: Answers) -> Self {
+        Self { answers, ..self }
+    }
+
+    /// Built-in variables, which answers may override
+    pub fn context(self, context: HashMap<String, String>) -> Self {
+        Self { context, ..self }
+    }
+
+    /// Template to pick when source is a collection
+    pub fn template_name(self, template_name: Option<String>) -> Self {
+        Self {
+            template_name,
+            ..self
+        }
+    }
+
+    /// Whether user can be prompted for anything
+    pub fn interactive(self, interactive: bool) -> Self {
+        Self {
+            interactive,
+            ..self
+        }
+    }
+
+    /// Only print what would be done, destination is left untouched
+    pub fn dry_run(self, dry_run: bool) -> Self {
+        Self { dry_run, ..self }
+    }
+
+    /// Policy for files already existing at destination
+    pub fn on_conflict(self, on_conflict: Conflict) -> Self {
+        Self {
+            on_conflict,
+            ..self
+        }
+    }
+
+    /// Checksum and signature which fetched archive must match
+    pub fn verification(self, verification: Verification) -> Self {
+        Self {
+            verification,
+            ..self
+        }
+    }
+
+    /// Limits and timeouts of http method
+    pub fn http(self, http: HttpConfig) -> Self {
+        Self { http, ..self }
+    }
+
+    /// Custom providers of git method
+    pub fn git(self, git: GitConfig) -> Self {
+        Self { git, ..self }
+    }
+
+    /// Branch, tag or commit of repository to use
+    pub fn reference(self, reference: Option<String>) -> Self {
+        Self { reference, ..self }
+    }
+
+    /// Credentials for private repositories and archives
+    pub fn auth(self, auth: AuthConfig) -> Self {
+        Self { auth, ..self }
+    }
+
+    /// Where fetched sources are kept between runs
+    pub fn cache(self, cache: Option<Cache>) -> Self {
+        Self { cache, ..self }
+    }
+
+    /// Use only cached sources, never touch network
+    pub fn offline(self, offline: bool) -> Self {
+        Self { offline, ..self }
+    }
+
+    /// Fetch again even if source is cached
+    pub fn refresh(self, refresh: bool) -> Self {
+        Self { refresh, ..self }
     }
 
     pub fn fetch_method<T: Methodical>(self, method: T) -> Result<Self> {
@@ -54,24 +215,65 @@ impl ManageBuilder {
 
         let destination = self.temporary.unwrap();
 
-        let method = method.to_method(
-            self.remote.clone().unwrap(),
-            destination.path().to_path_buf(),
-        );
+        let remote = self.remote.clone().unwrap();
+        let method = match remote.scheme() {
+            "file" => Protocol::Local.to_method(remote, destination.path().to_path_buf()),
+            _ => method.to_method(remote, destination.path().to_path_buf()),
+        };
+
+        if self.reference.is_some() && !matches!(method, Method::Git(_)) {
+            return Err(Error::ReferenceNeedsGit);
+        }
+
+        // Only archives can be checked against checksum or signature
+        let method = match method {
+            Method::Http(h) => Method::Http(
+                h.with_settings(self.http.clone())
+                    .with_auth(&self.auth)
+                    .with_verification(self.verification.clone()),
+            ),
+            Method::Git(g) if self.verification.is_empty() => Method::Git(
+                g.with_settings(self.git.clone())
+                    .with_auth(self.auth.clone())
+                    .with_reference(self.reference.clone()),
+            ),
+            _ if !self.verification.is_empty() => return Err(Error::VerificationNeedsArchive),
+            m => m,
+        };
 
         Ok(Self {
             method: Some(method),
-            remote: self.remote,
             temporary: Some(destination),
+            ..self
         })
     }
 
     pub fn build(self) -> Result<Manager> {
-        Ok(Manager::new(
+        let mut manager = Manager::new(
             self.remote.unwrap(),
             self.temporary.unwrap(),
             self.method.unwrap(),
-        ))
+        );
+
+        manager.globals = self
+            .context
+            .into_iter()
+            .map(|(k, v)| (k, v.into()))
+            .collect();
+        manager.globals.extend(self.answers);
+        manager.template_name = self.template_name;
+        manager.interactive = self.interactive;
+        manager.dry_run = self.dry_run;
+        manager.on_conflict = self.on_conflict;
+        manager.subdir = self.subdir;
+        manager.reference = self.reference;
+        // Cache holds extracted trees, archives can't be verified there
+        manager.cache = self.cache.filter(|_| self.verification.is_empty());
+        manager.verified = !self.verification.is_empty();
+        manager.offline = self.offline;
+        manager.refresh = self.refresh;
+
+        Ok(manager)
     }
 }
 
@@ -81,7 +283,18 @@ pub struct Manager {
     temporary: TempDir,
     method: Method,
     template: Configuration,
-    globals: HashMap<String, String>,
+    globals: Globals,
+    template_name: Option<String>,
+    interactive: bool,
+    dry_run: bool,
+    on_conflict: Conflict,
+    revision: Option<String>,
+    subdir: Option<PathBuf>,
+    reference: Option<String>,
+    cache: Option<Cache>,
+    offline: bool,
+    refresh: bool,
+    verified: bool,
 }
 
 impl Manager {
@@ -92,26 +305,129 @@ impl Manager {
             method,
             template: Default::default(),
             globals: HashMap::default(),
+            template_name: None,
+            interactive: true,
+            dry_run: false,
+            on_conflict: Conflict::default(),
+            revision: None,
+            subdir: None,
+            reference: None,
+            cache: None,
+            offline: false,
+            refresh: false,
+            verified: false,
         }
     }
 
+    pub fn remote(&self) -> &Url {
+        &self.remote
+    }
+
+    /// Commit which template was fetched at, if source has any
+    pub fn revision(&self) -> Option<&String> {
+        self.revision.as_ref()
+    }
+
     pub fn instantiate(self) -> Result<Self> {
-        self.method.fetch().map(|_| Self {
-            remote: self.remote,
-            temporary: self.temporary,
-            method: self.method,
-            template: self.template,
-            globals: self.globals,
-        })
+        // Local sources are already on disk, nothing to cache
+        if matches!(self.method, Method::Local(_)) {
+            let revision = self.method.fetch()?;
+            return Ok(Self { revision, ..self });
+        }
+
+        let mut remote = self.remote.clone();
+        let _ = remote.set_username("");
+        let _ = remote.set_password(None);
+
+        let source = remote.to_string();
+        let kind = self.method.kind();
+
+        // Stale copies are still better than nothing when offline
+        let cached = self.cache.as_ref().filter(|_| !self.refresh).and_then(|c| {
+            c.lookup(&source, kind, self.reference.as_deref())
+                .filter(|e| self.offline || c.fresh(e))
+                .map(|e| (c.fresh(&e), e))
+        });
+
+        let revision = match cached {
+            Some((fresh, entry)) => {
+                if !fresh {
+                    eprintln!(
+                        "{} cached copy is {} hours old and might be outdated",
+                        "warning:".yellow(),
+                        entry.age().as_secs() / 3600
+                    );
+                }
+
+                entry.restore(self.temporary.path())?;
+                match entry.meta.revision.as_ref() {
+                    Some(r) => eprintln!("{} {} at {}", "cached:".green(), source, r),
+                    None => eprintln!("{} {}", "cached:".green(), source),
+                }
+
+                entry.meta.revision
+            }
+            None if self.offline => return Err(Error::NotCached(source)),
+            None => {
+                let revision = self.method.fetch()?;
+
+                // Tell exact commit, so the result can be reproduced
+                if let Some(r) = revision.as_ref() {
+                    eprintln!("{} {} at {}", "fetched:".green(), source, r);
+                }
+
+                // Failing to cache is no reason to stop bootstrapping
+                if let Some(cache) = self.cache.as_ref() {
+                    let meta = Cache::meta(
+                        source.clone(),
+                        kind.to_owned(),
+                        self.reference.clone(),
+                        revision.clone(),
+                    );
+
+                    if let Err(e) = cache.store(meta, self.temporary.path()) {
+                        eprintln!("{} can't cache {}: {}", "warning:".yellow(), source, e);
+                    }
+                }
+
+                revision
+            }
+        };
+
+        Ok(Self { revision, ..self })
     }
 
     pub fn parse(self) -> Result<Self> {
-        Configuration::surely_template(self.temporary.path().to_path_buf(), 1).map(|t| Self {
+        let root = match self.subdir.as_ref() {
+            Some(subdir) => {
+                // Must stay inside of what was fetched
+                if subdir
+                    .components()
+                    .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+                {
+                    return Err(Error::InvalidFilePath(subdir.to_owned()));
+                }
+
+                Some(self.temporary.path().join(subdir))
+                    .filter(|p| p.is_dir())
+                    .ok_or(Error::NoSuchSubdirectory(subdir.to_owned()))?
+            }
+            None => self.temporary.path().to_path_buf(),
+        };
+
+        // Whatever is on local disk is user's own business
+        let trusted = self.verified || matches!(self.method, Method::Local(_));
+
+        Configuration::surely_template(
+            root,
+            1,
+            self.template_name.as_deref(),
+            self.interactive,
+            trusted,
+        )
+        .map(|t| Self {
             template: t,
-            remote: self.remote,
-            temporary: self.temporary,
-            method: self.method,
-            globals: self.globals,
+            ..self
         })
     }
 
@@ -120,39 +436,235 @@ impl Manager {
             .clone()
             .template()?
             .computable()
+            .interactive(self.interactive)
+            .dry_run(self.dry_run)
             .compute(&mut self.globals)
             .map(|_| self)
     }
 
+    /// Entries which copying would create or overwrite at destination,
+    /// directories conflict only when something else is in their place
+    pub fn preview(&self, destination: &Path) -> Result<Vec<(PathBuf, bool)>> {
+        let template = self.template.clone().template()?;
+
+        WalkDir::new(template.path())
+            .min_depth(1)
+            .into_iter()
+            .map(|e| {
+                let entry = e.map_err(|e| Error::IOError(e.into()))?;
+                let relative = entry
+                    .path()
+                    .strip_prefix(template.path())
+                    .map_err(|_| Error::InvalidFilePath(entry.path().to_path_buf()))?
+                    .to_path_buf();
+                let target = destination.join(&relative);
+                let exists = match entry.file_type().is_dir() {
+                    true => target.symlink_metadata().is_ok_and(|m| !m.is_dir()),
+                    false => target.symlink_metadata().is_ok(),
+                };
+
+                Ok((relative, exists))
+            })
+            .collect()
+    }
+
     pub fn recursively_copy(self, destination: PathBuf) -> Result<Self> {
-        if !Path::new(&destination).exists() {
-            fs::create_dir_all(&destination)?
+        let files = self.preview(&destination)?;
+        let source = self.template.clone().template()?.path().to_owned();
+
+        if self.dry_run {
+            for (file, exists) in files {
+                match exists {
+                    true => println!(
+                        "{} {}",
+                        format!("conflict ({})", self.on_conflict).red(),
+                        file.display()
+                    ),
+                    // Directories are implied by files, unless they're empty
+                    false if source.join(&file).symlink_metadata()?.is_dir() => {
+                        if fs::read_dir(source.join(&file))?.next().is_none() {
+                            println!("{} {}/", "create".green(), file.display());
+                        }
+                    }
+                    false => println!("{} {}", "create".green(), file.display()),
+                }
+            }
+
+            return Ok(self);
         }
 
-        CopyBuilder::new(self.template.clone().template()?.path(), destination)
-            .overwrite(true)
-            .overwrite_if_newer(true)
-            .overwrite_if_size_differs(true)
-            .run()?;
+        let conflicts = files.iter().filter(|(_, exists)| *exists).count();
+
+        // List everything upfront, before a single file is written
+        if conflicts > 0 {
+            eprintln!(
+                "{} these files already exist at destination:",
+                "conflict:".yellow()
+            );
+            files
+                .iter()
+                .filter(|(_, exists)| *exists)
+                .for_each(|(file, _)| eprintln!("  {}", file.display()));
+
+            if self.on_conflict == Conflict::Abort
+                || (self.on_conflict == Conflict::Prompt && !self.interactive)
+            {
+                return Err(Error::DestinationConflicts(conflicts));
+            }
+        }
+
+        fs::create_dir_all(&destination)?;
+
+        for (file, exists) in files {
+            let origin = source.join(&file);
+            let target = destination.join(&file);
+            let kind = origin.symlink_metadata()?.file_type();
+
+            if exists && !self.resolve(&target)? {
+                continue;
+            }
+
+            // Writing through an old symlink would land outside of destination
+            if target.symlink_metadata().is_ok_and(|m| !m.is_dir()) {
+                fs::remove_file(&target)?;
+            }
+
+            if kind.is_dir() {
+                fs::create_dir_all(&target)?;
+                continue;
+            }
+
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            if kind.is_symlink() {
+                link(&origin, &target)?;
+                continue;
+            }
+
+            fs::copy(origin, &target)?;
+        }
 
         Ok(self)
     }
+
+    /// Decide whether existing file should be written over
+    fn resolve(&self, target: &Path) -> Result<bool> {
+        match self.on_conflict {
+            Conflict::Abort | Conflict::Skip => Ok(false),
+            Conflict::Overwrite => Ok(true),
+            Conflict::Prompt => inquire::Confirm::new(&format!("Overwrite {}?", target.display()))
+                .with_default(false)
+                .prompt()
+                .map_err(Error::CantParseUserPrompt),
+            Conflict::Backup => {
+                fs::rename(target, backup(target))?;
+
+                Ok(true)
+            }
+        }
+    }
+}
+
+/// First of `*.bak`, `*.bak.1`, `*.bak.2`... that isn't taken yet
+fn backup(target: &Path) -> PathBuf {
+    let named = |suffix: String| {
+        let mut name = target.as_os_str().to_owned();
+        name.push(suffix);
+        PathBuf::from(name)
+    };
+
+    let mut candidate = named(".bak".to_owned());
+    let mut n = 0;
+
+    while candidate.symlink_metadata().is_ok() {
+        n += 1;
+        candidate = named(format!(".bak.{n}"));
+    }
+
+    candidate
 }
 
-/// For HashMap to implement string search
+/// Split `source//path/inside` into source and path inside of it
+fn split_subdir(source: &str) -> (String, Option<PathBuf>) {
+    // Double slash of scheme doesn't count
+    let start = source.find("://").map_or(0, |i| i + 3);
+
+    let Some(at) = source[start..].find("//").map(|i| i + start) else {
+        return (source.to_owned(), None);
+    };
+
+    // Query and fragment belong to the source, not path
+    let rest = &source[at + 2..];
+    let (path, suffix) = rest.split_at(rest.find(['?', '#']).unwrap_or(rest.len()));
+
+    (
+        format!("{}{}", &source[..at], suffix),
+        Some(PathBuf::from(path)).filter(|p| !p.as_os_str().is_empty()),
+    )
+}
+
+/// For globals to implement string search
 pub trait Glubtastic {
     fn globs<T: AsRef<str>>(&self, text: T) -> Vec<String>;
+    fn interpolate<T: AsRef<str>>(&self, text: T) -> Result<String>;
+    fn substitute<T: AsRef<str>>(&self, text: T) -> String;
 }
 
-impl Glubtastic for HashMap<String, String> {
+impl Glubtastic for Globals {
     /// Catch all @variable@ references within a string
     fn globs<T: AsRef<str>>(&self, text: T) -> Vec<String> {
         REGEX
             .captures_iter(text.as_ref())
             .map(|caps| {
-                let (_, [input]) = caps.extract();
+                let (_, [input, _]) = caps.extract();
                 input.to_owned()
             })
             .collect::<Vec<String>>()
     }
+
+    /// Replace all @variable@ references with their values,
+    /// passed through filters of each reference
+    fn interpolate<T: AsRef<str>>(&self, text: T) -> Result<String> {
+        let text = text.as_ref();
+        let mut result = String::with_capacity(text.len());
+        let mut last = 0;
+
+        for caps in REGEX.captures_iter(text) {
+            let (_, [name, filters]) = caps.extract();
+            let filters = Apply::filters(filters)?;
+
+            // Missing variable is fine as long as there's a fallback
+            let value = match self.get(name) {
+                Some(value) => value.to_string(),
+                None if filters.has_default() => String::new(),
+                None => return Err(Error::NoSuchVariable(name.to_owned())),
+            };
+
+            let range = caps.get_match().range();
+            result.push_str(&text[last..range.start]);
+            result.push_str(&filters.execute(value));
+            last = range.end;
+        }
+
+        result.push_str(&text[last..]);
+
+        Ok(result)
+    }
+
+    /// Replace @variable@ references which are known, leaving
+    /// anything else alone, as arbitrary files may contain @s
+    fn substitute<T: AsRef<str>>(&self, text: T) -> String {
+        REGEX
+            .replace_all(text.as_ref(), |caps: &regex::Captures| {
+                let (whole, [name, filters]) = caps.extract();
+
+                match (self.get(name), Apply::filters(filters)) {
+                    (Some(value), Ok(filters)) => filters.execute(value),
+                    _ => whole.to_owned(),
+                }
+            })
+            .into_owned()
+    }
 }
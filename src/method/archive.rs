@@ -0,0 +1,545 @@
+use crate::error::{BleurError, Result};
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::{Component, Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Archive {
+    Zip,
+    TarGz,
+    TarXz,
+    TarZst,
+}
+
+impl Archive {
+    /// Guess from Content-Type header of response
+    pub fn from_content_type<T: AsRef<str>>(content_type: T) -> Option<Self> {
+        // Parameters like "; charset=..." are irrelevant
+        let mime = content_type.as_ref().split(';').next()?.trim();
+
+        match mime {
+            "application/zip" | "application/x-zip-compressed" => Some(Self::Zip),
+            "application/gzip" | "application/x-gzip" | "application/x-tgz" => Some(Self::TarGz),
+            "application/x-xz" => Some(Self::TarXz),
+            "application/zstd" | "application/x-zstd" => Some(Self::TarZst),
+            _ => None,
+        }
+    }
+
+    /// Guess from extension of file name
+    pub fn from_name<T: AsRef<str>>(name: T) -> Option<Self> {
+        let name = name.as_ref().to_lowercase();
+
+        [
+            (".zip", Self::Zip),
+            (".tar.gz", Self::TarGz),
+            (".tgz", Self::TarGz),
+            (".tar.xz", Self::TarXz),
+            (".txz", Self::TarXz),
+            (".tar.zst", Self::TarZst),
+            (".tzst", Self::TarZst),
+        ]
+        .into_iter()
+        .find(|(extension, _)| name.ends_with(extension))
+        .map(|(_, archive)| archive)
+    }
+
+    /// Guess from first bytes of downloaded file
+    pub fn from_magic(path: &Path) -> Option<Self> {
+        let mut magic = [0u8; 6];
+        let read = File::open(path).and_then(|mut f| f.read(&mut magic)).ok()?;
+
+        match &magic[..read] {
+            [0x50, 0x4b, 0x03, 0x04, ..] => Some(Self::Zip),
+            [0x1f, 0x8b, ..] => Some(Self::TarGz),
+            [0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00] => Some(Self::TarXz),
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Self::TarZst),
+            _ => None,
+        }
+    }
+
+    /// Extract archive to destination, skipping top-level folder
+    pub fn extract(&self, archive: &Path, destination: &Path) -> Result<()> {
+        match self {
+            Self::Zip => unzip(archive, destination),
+            Self::TarGz => untar(flate2::read::GzDecoder::new(open(archive)?), destination),
+            Self::TarXz => untar(liblzma::read::XzDecoder::new(open(archive)?), destination),
+            Self::TarZst => untar(
+                zstd::Decoder::new(open(archive)?).map_err(BleurError::CantOpenArchive)?,
+                destination,
+            ),
+        }
+    }
+}
+
+fn open(path: &Path) -> Result<File> {
+    File::open(path).map_err(BleurError::CantOpenArchive)
+}
+
+/// Path of entry relative to destination without base folder,
+/// refusing anything that could escape the destination
+fn strip_base(path: &Path, destination: &Path) -> Result<PathBuf> {
+    let unsafe_entry = || BleurError::UnsafeArchiveEntry(path.to_string_lossy().to_string());
+
+    if path
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(unsafe_entry());
+    }
+
+    Ok(path
+        .components()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .skip(1)
+        .fold(destination.to_path_buf(), |p, c| p.join(c)))
+}
+
+/// Make sure link placed at `outpath` resolves inside destination
+fn check_link(outpath: &Path, target: &Path, destination: &Path) -> Result<()> {
+    let escapes = || {
+        BleurError::UnsafeArchiveLink(
+            outpath
+                .strip_prefix(destination)
+                .unwrap_or(outpath)
+                .to_string_lossy()
+                .to_string(),
+            target.to_string_lossy().to_string(),
+        )
+    };
+
+    if target.is_absolute() {
+        return Err(escapes());
+    }
+
+    let mut resolved = outpath.parent().ok_or_else(escapes)?.to_path_buf();
+
+    for component in target.components() {
+        match component {
+            Component::Normal(c) => {
+                resolved.push(c);
+
+                // Links extracted earlier may point anywhere
+                if is_link(&resolved) {
+                    return Err(escapes());
+                }
+            }
+            Component::CurDir => {}
+            Component::ParentDir if resolved != destination => {
+                resolved.pop();
+            }
+            _ => return Err(escapes()),
+        }
+    }
+
+    resolved
+        .starts_with(destination)
+        .then_some(())
+        .ok_or_else(escapes)
+}
+
+fn is_link(path: &Path) -> bool {
+    path.symlink_metadata()
+        .is_ok_and(|m| m.file_type().is_symlink())
+}
+
+/// Refuse entries placed at or under links extracted earlier,
+/// as writing them would follow those links wherever they point
+fn unlinked(outpath: &Path, root: &Path) -> Result<()> {
+    match outpath.ancestors().take_while(|a| *a != root).any(is_link) {
+        true => Err(BleurError::UnsafeArchiveEntry(
+            outpath
+                .strip_prefix(root)
+                .unwrap_or(outpath)
+                .to_string_lossy()
+                .to_string(),
+        )),
+        false => Ok(()),
+    }
+}
+
+/// Create parents of entry, making sure they really are inside
+/// of destination once every link on the way is resolved
+fn create_parent(outpath: &Path, destination: &Path) -> Result<()> {
+    unlinked(outpath, destination)?;
+
+    let Some(parent) = outpath.parent() else {
+        return Ok(());
+    };
+
+    fs::create_dir_all(parent)
+        .map_err(|_| BleurError::CantCreateDirectory(parent.to_path_buf()))?;
+
+    let inside = match (parent.canonicalize(), destination.canonicalize()) {
+        (Ok(parent), Ok(destination)) => parent.starts_with(destination),
+        _ => false,
+    };
+
+    inside.then_some(()).ok_or_else(|| {
+        BleurError::UnsafeArchiveEntry(
+            outpath
+                .strip_prefix(destination)
+                .unwrap_or(outpath)
+                .to_string_lossy()
+                .to_string(),
+        )
+    })
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, outpath: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(target, outpath)
+        .map_err(|e| BleurError::CantExtractEntry(outpath.to_path_buf(), e))
+}
+
+#[cfg(not(unix))]
+fn symlink(target: &Path, outpath: &Path) -> Result<()> {
+    // No portable way of linking, so keep a copy instead
+    fs::copy(outpath.parent().unwrap_or(outpath).join(target), outpath)
+        .map(|_| ())
+        .map_err(|e| BleurError::CantExtractEntry(outpath.to_path_buf(), e))
+}
+
+fn unzip(path: &Path, destination: &Path) -> Result<()> {
+    let file = fs::File::open(path).map_err(BleurError::CantOpenArchive)?;
+    let mut archive = zip::ZipArchive::new(file)?;
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+
+        // Skip base folder, no need.
+        let outpath = strip_base(Path::new(file.name()), destination)?;
+        if outpath == destination {
+            continue;
+        }
+
+        create_parent(&outpath, destination)?;
+
+        if file.is_dir() {
+            fs::create_dir_all(&outpath)
+                .map_err(|_| BleurError::CantCreateDirectory(outpath.clone()))?;
+        } else if file.is_symlink() {
+            let mut target = String::new();
+            file.read_to_string(&mut target)
+                .map_err(|e| BleurError::CantExtractEntry(outpath.clone(), e))?;
+
+            check_link(&outpath, Path::new(&target), destination)?;
+            symlink(Path::new(&target), &outpath)?;
+
+            // Permissions of links are meaningless
+            continue;
+        } else {
+            let mut outfile = fs::File::create(&outpath)
+                .map_err(|e| BleurError::CantExtractEntry(outpath.clone(), e))?;
+            io::copy(&mut file, &mut outfile)
+                .map_err(|e| BleurError::CantExtractEntry(outpath.clone(), e))?;
+        }
+
+        // Get and Set permissions
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            if let Some(mode) = file.unix_mode() {
+                fs::set_permissions(&outpath, fs::Permissions::from_mode(mode))
+                    .map_err(|_| BleurError::CantSetPermissions(outpath.clone()))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn untar<R: Read>(reader: R, destination: &Path) -> Result<()> {
+    let mut archive = tar::Archive::new(reader);
+    archive.set_preserve_permissions(true);
+
+    // Unpacked as is first, tar makes sure nothing leaves staging
+    let staging = tempfile::Builder::new()
+        .prefix(".bleur-")
+        .tempdir_in(destination)
+        .map_err(|_| BleurError::CantCreateDirectory(destination.to_path_buf()))?;
+
+    for entry in archive.entries().map_err(BleurError::CantUnArchiveTar)? {
+        let mut entry = entry.map_err(BleurError::CantUnArchiveTar)?;
+        let kind = entry.header().entry_type();
+
+        // GitHub tarballs carry commit id in a pax global header
+        if kind == tar::EntryType::XGlobalHeader {
+            continue;
+        }
+
+        let path = entry
+            .path()
+            .map_err(BleurError::CantUnArchiveTar)?
+            .into_owned();
+        let unsafe_entry = || BleurError::UnsafeArchiveEntry(path.to_string_lossy().to_string());
+
+        // Skip base folder, no need.
+        if strip_base(&path, destination)? == destination {
+            continue;
+        }
+
+        let unpacked = normalize(&path, staging.path());
+        let base = path
+            .components()
+            .find(|c| matches!(c, Component::Normal(_)))
+            .map(|c| staging.path().join(c))
+            .ok_or_else(unsafe_entry)?;
+
+        unlinked(&unpacked, staging.path())?;
+
+        match kind {
+            tar::EntryType::Symlink => {
+                let target = entry
+                    .link_name()
+                    .map_err(BleurError::CantUnArchiveTar)?
+                    .ok_or_else(unsafe_entry)?;
+
+                check_link(&unpacked, &target, &base)?;
+            }
+            // Hard links point to other entries of archive, not disk
+            tar::EntryType::Link => {
+                let target = entry
+                    .link_name()
+                    .map_err(BleurError::CantUnArchiveTar)?
+                    .ok_or_else(unsafe_entry)?;
+
+                strip_base(&target, destination)?;
+
+                if !normalize(&target, staging.path()).starts_with(&base) {
+                    return Err(BleurError::UnsafeArchiveLink(
+                        path.to_string_lossy().to_string(),
+                        target.to_string_lossy().to_string(),
+                    ));
+                }
+            }
+            _ => {}
+        }
+
+        // Sets permissions as well
+        let unpacked = entry
+            .unpack_in(staging.path())
+            .map_err(|e| BleurError::CantExtractEntry(unpacked.clone(), e))?;
+
+        if !unpacked {
+            return Err(unsafe_entry());
+        }
+    }
+
+    // Contents of base folder is what's needed
+    for base in fs::read_dir(staging.path()).map_err(BleurError::CantUnArchiveTar)? {
+        let base = base.map_err(BleurError::CantUnArchiveTar)?;
+
+        if base
+            .file_type()
+            .map_err(BleurError::CantUnArchiveTar)?
+            .is_dir()
+        {
+            merge(&base.path(), destination)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Entry path placed under root, without `.` parts
+fn normalize(path: &Path, root: &Path) -> PathBuf {
+    path.components()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .fold(root.to_path_buf(), |p, c| p.join(c))
+}
+
+/// Move everything from one directory into another, descending
+/// into directories both of them have
+fn merge(from: &Path, to: &Path) -> Result<()> {
+    for entry in fs::read_dir(from).map_err(BleurError::CantUnArchiveTar)? {
+        let entry = entry.map_err(BleurError::CantUnArchiveTar)?;
+        let target = to.join(entry.file_name());
+        let failed = |e| BleurError::CantExtractEntry(target.clone(), e);
+
+        if entry.file_type().map_err(failed)?.is_dir()
+            && target.symlink_metadata().is_ok_and(|m| m.is_dir())
+        {
+            merge(&entry.path(), &target)?;
+            continue;
+        }
+
+        fs::rename(entry.path(), &target).map_err(failed)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::{tempdir, TempDir};
+    use zip::write::SimpleFileOptions;
+
+    enum Entry<'a> {
+        File(&'a str),
+        Dir(&'a str),
+        Symlink(&'a str, &'a str),
+        Hardlink(&'a str, &'a str),
+    }
+
+    /// Scratch directory with empty destination inside of it
+    fn scratch() -> (TempDir, PathBuf) {
+        let root = tempdir().unwrap();
+        let destination = root.path().join("destination");
+        fs::create_dir(&destination).unwrap();
+
+        (root, destination)
+    }
+
+    /// Header names are written as is, as tar refuses to build malicious ones
+    fn tar(root: &Path, entries: &[Entry]) -> PathBuf {
+        let path = root.join("archive.tar.gz");
+        let encoder = flate2::write::GzEncoder::new(
+            File::create(&path).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+
+        for entry in entries {
+            let mut header = tar::Header::new_old();
+            let (name, kind, link) = match entry {
+                Entry::File(name) => (name, tar::EntryType::Regular, None),
+                Entry::Dir(name) => (name, tar::EntryType::Directory, None),
+                Entry::Symlink(name, link) => (name, tar::EntryType::Symlink, Some(link)),
+                Entry::Hardlink(name, link) => (name, tar::EntryType::Link, Some(link)),
+            };
+            let data: &[u8] = match kind {
+                tar::EntryType::Regular => b"pwned",
+                _ => b"",
+            };
+
+            header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+            if let Some(link) = link {
+                header.as_old_mut().linkname[..link.len()].copy_from_slice(link.as_bytes());
+            }
+            header.set_entry_type(kind);
+            header.set_mode(0o755);
+            header.set_size(data.len() as u64);
+            header.set_cksum();
+
+            builder.append(&header, data).unwrap();
+        }
+
+        builder.into_inner().unwrap().finish().unwrap();
+
+        path
+    }
+
+    fn zip(root: &Path, entries: &[Entry]) -> PathBuf {
+        let path = root.join("archive.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&path).unwrap());
+        let options = SimpleFileOptions::default();
+
+        for entry in entries {
+            match entry {
+                Entry::File(name) => {
+                    writer.start_file(*name, options).unwrap();
+                    writer.write_all(b"pwned").unwrap();
+                }
+                Entry::Dir(name) => writer.add_directory(*name, options).unwrap(),
+                Entry::Symlink(name, link) => writer.add_symlink(*name, *link, options).unwrap(),
+                Entry::Hardlink(..) => unreachable!("zip has no hard links"),
+            }
+        }
+
+        writer.finish().unwrap();
+
+        path
+    }
+
+    #[test]
+    fn extracts_without_base_folder() {
+        for archive in [Archive::TarGz, Archive::Zip] {
+            let (root, destination) = scratch();
+            let entries = [
+                Entry::Dir("base/"),
+                Entry::Dir("base/src/"),
+                Entry::File("base/src/main.rs"),
+                Entry::Symlink("base/main.rs", "src/main.rs"),
+            ];
+            let path = match archive {
+                Archive::Zip => zip(root.path(), &entries),
+                _ => tar(root.path(), &entries),
+            };
+
+            archive.extract(&path, &destination).unwrap();
+
+            assert_eq!(
+                fs::read_to_string(destination.join("main.rs")).unwrap(),
+                "pwned"
+            );
+            assert_eq!(fs::read_dir(&destination).unwrap().count(), 2);
+        }
+    }
+
+    #[test]
+    fn refuses_escaping_entries() {
+        for archive in [Archive::TarGz, Archive::Zip] {
+            let (root, destination) = scratch();
+            let outside = root.path().join("outside");
+            let cases = [
+                vec![Entry::File("base/../../outside")],
+                vec![Entry::File(outside.to_str().unwrap())],
+                vec![Entry::Symlink("base/link", "../../outside")],
+                vec![Entry::Symlink("base/link", outside.to_str().unwrap())],
+                // Every link is fine on its own, together they lead out
+                vec![
+                    Entry::Dir("base/a/"),
+                    Entry::Symlink("base/a/b", ".."),
+                    Entry::Symlink("base/a/b/c", "../outside"),
+                    Entry::File("base/a/b/c/file"),
+                ],
+                vec![
+                    Entry::Dir("base/d/"),
+                    Entry::Symlink("base/d/up", ".."),
+                    Entry::Symlink("base/link", "d/up/../outside"),
+                ],
+            ];
+
+            for entries in cases {
+                let path = match archive {
+                    Archive::Zip => zip(root.path(), &entries),
+                    _ => tar(root.path(), &entries),
+                };
+
+                assert!(archive.extract(&path, &destination).is_err());
+                assert!(!outside.exists());
+            }
+        }
+    }
+
+    #[test]
+    fn refuses_escaping_hard_links() {
+        let (root, destination) = scratch();
+        let outside = root.path().join("outside");
+        fs::write(&outside, "secret").unwrap();
+
+        let cases = [
+            vec![Entry::Hardlink("base/link", outside.to_str().unwrap())],
+            vec![Entry::Hardlink("base/link", "base/../../outside")],
+            vec![Entry::Hardlink("base/link", "other/file")],
+            vec![
+                Entry::Symlink("base/up", "."),
+                Entry::Hardlink("base/link", "base/up/../../outside"),
+            ],
+        ];
+
+        for entries in cases {
+            let path = tar(root.path(), &entries);
+
+            assert!(Archive::TarGz.extract(&path, &destination).is_err());
+            assert!(!destination.join("link").exists());
+            assert_eq!(fs::read_to_string(&outside).unwrap(), "secret");
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use crate::config::AuthConfig;
+use git2::{Cred, CredentialType, FetchOptions, RemoteCallbacks};
+use url::Url;
+
+/// Ways of authenticating, tried one after another as
+/// libgit2 keeps asking until one works or all fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Attempt {
+    Agent,
+    Key,
+    Token,
+    Helper,
+    Default,
+    Exhausted,
+}
+
+impl Attempt {
+    fn next(self) -> Self {
+        match self {
+            Self::Agent => Self::Key,
+            Self::Key => Self::Token,
+            Self::Token => Self::Helper,
+            Self::Helper => Self::Default,
+            Self::Default | Self::Exhausted => Self::Exhausted,
+        }
+    }
+}
+
+/// Fetch options with credentials from ssh-agent, ssh key,
+/// tokens and git credential helpers
+pub fn fetch_options(auth: &AuthConfig, depth: Option<i32>) -> FetchOptions<'_> {
+    let mut attempt = Attempt::Agent;
+    let mut callbacks = RemoteCallbacks::new();
+
+    callbacks.credentials(move |url, username, allowed| {
+        let user = username.unwrap_or("git");
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(|h| h.to_owned()))
+            .unwrap_or_default();
+
+        // Ssh asks for user name first, when url has none
+        if allowed.contains(CredentialType::USERNAME) {
+            return Cred::username(user);
+        }
+
+        while attempt != Attempt::Exhausted {
+            let current = attempt;
+            attempt = attempt.next();
+
+            let credential = match current {
+                Attempt::Agent if allowed.contains(CredentialType::SSH_KEY) => {
+                    Cred::ssh_key_from_agent(user).ok()
+                }
+                Attempt::Key if allowed.contains(CredentialType::SSH_KEY) => auth
+                    .ssh_key
+                    .as_ref()
+                    .and_then(|key| Cred::ssh_key(user, None, key, None).ok()),
+                Attempt::Token if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) => auth
+                    .credential(&host)
+                    .map(|c| c.userpass(&host))
+                    .and_then(|(name, secret)| Cred::userpass_plaintext(&name, &secret).ok()),
+                Attempt::Helper if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) => {
+                    git2::Config::open_default()
+                        .ok()
+                        .and_then(|config| Cred::credential_helper(&config, url, username).ok())
+                }
+                Attempt::Default if allowed.contains(CredentialType::DEFAULT) => {
+                    Cred::default().ok()
+                }
+                _ => None,
+            };
+
+            if let Some(credential) = credential {
+                return Ok(credential);
+            }
+        }
+
+        // Nothing sensitive in here, only that nothing worked
+        Err(git2::Error::from_str(&format!(
+            "no credentials were accepted for {host}"
+        )))
+    });
+
+    let mut options = FetchOptions::new();
+    options.remote_callbacks(callbacks);
+
+    if let Some(depth) = depth {
+        options.depth(depth);
+    }
+
+    options
+}
@@ -1,42 +1,147 @@
+pub mod auth;
 pub mod provider;
 
+use crate::config::{AuthConfig, GitConfig};
 use crate::error::{BleurError, Result};
+use crate::method::git::auth::fetch_options;
 use crate::method::git::provider::Provider;
 use crate::method::Fetchable;
-use git2::FetchOptions;
-use std::path::PathBuf;
+use git2::{build::CheckoutBuilder, build::RepoBuilder, Oid, Repository};
+use std::path::{Path, PathBuf};
 use url::Url;
 
+/// Where requested revision is fetched to
+static TARGET_REFERENCE: &str = "refs/bleur/target";
+
 #[derive(Debug)]
 pub struct Git {
     url: Url,
     path: PathBuf,
+    settings: GitConfig,
+    reference: Option<String>,
+    auth: AuthConfig,
 }
 
 impl Git {
     pub fn new(url: Url, path: PathBuf) -> Self {
-        Self { url, path }
+        Self {
+            url,
+            path,
+            settings: GitConfig::default(),
+            reference: None,
+            auth: AuthConfig::default(),
+        }
+    }
+
+    pub fn with_settings(self, settings: GitConfig) -> Self {
+        Self { settings, ..self }
     }
 
-    fn clone(&self) -> Result<()> {
-        let mut options = FetchOptions::new();
-        options.depth(1);
+    pub fn with_auth(self, auth: AuthConfig) -> Self {
+        Self { auth, ..self }
+    }
+
+    /// Branch, tag or commit to checkout, wins over one in url
+    pub fn with_reference(self, reference: Option<String>) -> Self {
+        Self { reference, ..self }
+    }
 
+    fn clone(&self) -> Result<String> {
         let provider = Provider::from_url(self.url.clone())?;
-        provider.fetch_repo(options, &self.path).map(|_| ())?;
+        let link = provider.link(&self.settings.providers)?;
+
+        let repository = match self.reference.clone().or(provider.reference()) {
+            Some(reference) => checkout(&link, &reference, &self.path, &self.auth)?,
+            None => RepoBuilder::new()
+                .fetch_options(fetch_options(&self.auth, Some(1)))
+                .clone(&link, &self.path)
+                .map_err(BleurError::GitError)?,
+        };
+
+        let revision = repository
+            .head()
+            .and_then(|h| h.peel_to_commit())
+            .map_err(BleurError::GitError)?
+            .id()
+            .to_string();
+
+        drop(repository);
 
         std::fs::remove_dir_all(self.path.as_path().join(".git"))
             .map_err(|_| BleurError::CantDeleteGitDirectorty)?;
 
-        Ok(())
+        Ok(revision)
     }
 }
 
+/// Fetch exactly the given revision and check it out
+fn checkout(link: &str, reference: &str, path: &Path, auth: &AuthConfig) -> Result<Repository> {
+    let repository = Repository::init(path).map_err(BleurError::GitError)?;
+    let oid = resolve(&repository, link, reference, auth)?;
+
+    {
+        let commit = repository.find_commit(oid).map_err(BleurError::GitError)?;
+
+        repository
+            .checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().force()))
+            .and_then(|_| repository.set_head_detached(oid))
+            .map_err(BleurError::GitError)?;
+    }
+
+    Ok(repository)
+}
+
+fn resolve(repository: &Repository, link: &str, reference: &str, auth: &AuthConfig) -> Result<Oid> {
+    let mut remote = repository
+        .remote_anonymous(link)
+        .map_err(BleurError::GitError)?;
+
+    // Try shallow first: as branch, as tag, then as full commit sha
+    for source in [
+        format!("refs/heads/{reference}"),
+        format!("refs/tags/{reference}"),
+        reference.to_owned(),
+    ] {
+        let refspec = format!("+{source}:{TARGET_REFERENCE}");
+        if remote
+            .fetch(&[&refspec], Some(&mut fetch_options(auth, Some(1))), None)
+            .is_err()
+        {
+            continue;
+        }
+
+        if let Ok(commit) = repository
+            .find_reference(TARGET_REFERENCE)
+            .and_then(|r| r.peel_to_commit())
+        {
+            return Ok(commit.id());
+        }
+    }
+
+    // Server doesn't allow any of above or sha is abbreviated,
+    // so the whole history is needed to find the revision
+    remote
+        .fetch(
+            &[
+                "+refs/heads/*:refs/remotes/origin/*",
+                "+refs/tags/*:refs/tags/*",
+            ],
+            Some(&mut fetch_options(auth, None)),
+            None,
+        )
+        .map_err(BleurError::GitError)?;
+
+    repository
+        .revparse_single(reference)
+        .or_else(|_| repository.revparse_single(&format!("origin/{reference}")))
+        .and_then(|o| o.peel_to_commit())
+        .map(|c| c.id())
+        .map_err(|_| BleurError::NoSuchRevision(reference.to_owned()))
+}
+
 impl Fetchable for Git {
     // https://docs.rs/git2/latest/git2/build/struct.RepoBuilder.html
-    fn fetch(&self) -> Result<()> {
-        self.clone()?;
-
-        Ok(())
+    fn fetch(&self) -> Result<Option<String>> {
+        self.clone().map(Some)
     }
 }
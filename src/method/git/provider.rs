@@ -1,103 +1,163 @@
-use std::path::Path;
+use std::collections::HashMap;
 
 use crate::{Error, Result};
-use git2::{build::RepoBuilder, FetchOptions, Repository};
 use url::Url;
 
+/// Segment which separates repository path from branch,
+/// same as in GitLab's own urls: group/sub/repo/-/branch
+static BRANCH_SEPARATOR: &str = "-";
+
+/// Query parameter which pins revision: github:owner/repo?ref=v1.2.0
+static REFERENCE_QUERY: &str = "ref";
+
 pub enum Provider {
-    Branch {
-        provider: String,
-        owner: String,
-        repo: String,
-        branch: String,
-    },
-    Branchless {
+    Shorthand {
         provider: String,
         owner: String,
         repo: String,
+        reference: Option<String>,
     },
     Other {
         url: String,
+        reference: Option<String>,
     },
 }
 
-fn provider_link(scheme: String, owner: String, repo: String) -> Result<String> {
-    Ok(match scheme.as_str() {
-        "github" => format!("https://github.com/{owner}/{repo}"),
-        // I won't support GitLab's obscure format, it's too unpredictable
-        _ => return Err(Error::UnknownGitProvider(scheme.to_owned())),
+/// Url templates of providers bleur knows out of the box
+fn builtin(scheme: &str) -> Option<&'static str> {
+    Some(match scheme {
+        "github" => "https://github.com/{owner}/{repo}",
+        "gitlab" => "https://gitlab.com/{owner}/{repo}",
+        "codeberg" => "https://codeberg.org/{owner}/{repo}",
+        "sourcehut" | "sr" => "https://git.sr.ht/~{owner}/{repo}",
+        "bitbucket" => "https://bitbucket.org/{owner}/{repo}",
+        _ => return None,
     })
 }
 
+/// Providers where every segment might be a (sub)group
+fn nested(scheme: &str) -> bool {
+    scheme == "gitlab"
+}
+
+fn provider_link(
+    scheme: &str,
+    owner: &str,
+    repo: &str,
+    custom: &HashMap<String, String>,
+) -> Result<String> {
+    // User's own prefixes may override built-in ones
+    let template = custom
+        .get(scheme)
+        .map(|t| t.as_str())
+        .or_else(|| builtin(scheme))
+        .ok_or(Error::UnknownGitProvider(scheme.to_owned()))?;
+
+    // Sourcehut's tilde is part of its template already, but
+    // elsewhere ~user may well be a real path segment
+    let owner = match template.contains("~{owner}") {
+        true => owner.trim_start_matches('~'),
+        false => owner,
+    };
+
+    Ok(template.replace("{owner}", owner).replace("{repo}", repo))
+}
+
+/// Take ?ref=... or #... out of url, query wins over fragment
+fn split_reference(mut url: Url) -> (Url, Option<String>) {
+    let query = url
+        .query_pairs()
+        .find(|(k, _)| k == REFERENCE_QUERY)
+        .map(|(_, v)| v.into_owned());
+    let fragment = url.fragment().map(|f| f.to_owned());
+
+    let rest: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(k, _)| k != REFERENCE_QUERY)
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+
+    url.set_fragment(None);
+    url.set_query(None);
+    if !rest.is_empty() {
+        url.query_pairs_mut().extend_pairs(rest);
+    }
+
+    (url, query.or(fragment).filter(|r| !r.is_empty()))
+}
+
 impl Provider {
     pub fn from_url(url: Url) -> Result<Self> {
+        let (url, pinned) = split_reference(url);
+
         // example:owner/repo/branch
         // ---^---------------------
-        if url.scheme() == "https" || url.scheme() == "http" {
+        if ["https", "http", "ssh", "git"].contains(&url.scheme()) {
             return Ok(Self::Other {
                 url: url.to_string(),
+                reference: pinned,
             });
         }
 
-        let path: Vec<String> = url.path().split('/').map(|i| i.to_owned()).collect();
+        let path: Vec<String> = url
+            .path()
+            .split('/')
+            .filter(|p| !p.is_empty())
+            .map(|i| i.to_owned())
+            .collect();
 
-        // example:owner/repo/branch
-        // ----------^--------------
-        let owner = path
-            .first()
-            .map(|p| p.to_owned())
-            .ok_or(Error::InvalidRepositoryOwner(url.to_string()))?;
+        // example:group/sub/repo/-/branch
+        // ------------------------^------
+        let (path, branch) = match path.iter().position(|p| p == BRANCH_SEPARATOR) {
+            Some(i) => (
+                path[..i].to_vec(),
+                Some(path[i + 1..].join("/")).filter(|b| !b.is_empty()),
+            ),
+            None if nested(url.scheme()) => (path, None),
+            None => (path.iter().take(2).cloned().collect(), path.get(2).cloned()),
+        };
 
         // example:owner/repo/branch
         // ---------------^---------
         let repo = path
-            .get(1)
+            .last()
+            .filter(|_| path.len() > 1)
             .map(|p| p.to_owned())
             .ok_or(Error::InvalidRepositoryName(url.to_string()))?;
 
         // example:owner/repo/branch
-        // ---------------------^---
-        let branch = path.get(2).map(|p| p.to_owned());
+        // ----------^--------------
+        let owner = Some(path[..path.len() - 1].join("/"))
+            .filter(|o| !o.is_empty())
+            .ok_or(Error::InvalidRepositoryOwner(url.to_string()))?;
 
-        Ok(match branch {
-            Some(b) => Self::Branch {
-                repo,
-                owner,
-                branch: b,
-                provider: url.scheme().to_owned(),
-            },
-            None => Self::Branchless {
-                repo,
-                owner,
-                provider: url.scheme().to_owned(),
-            },
+        // example:owner/repo/branch?ref=v1.2.0
+        // ---------------------^--------^-----
+        Ok(Self::Shorthand {
+            repo,
+            owner,
+            reference: pinned.or(branch),
+            provider: url.scheme().to_owned(),
         })
     }
 
-    pub fn fetch_repo(self, options: FetchOptions, path: &Path) -> Result<Repository> {
+    /// Cloneable url of repository
+    pub fn link(&self, custom: &HashMap<String, String>) -> Result<String> {
         match self {
-            Self::Branch {
-                provider,
-                owner,
-                repo,
-                branch,
-            } => RepoBuilder::new()
-                .fetch_options(options)
-                .branch(&branch)
-                .clone(&provider_link(provider, owner, repo)?, path)
-                .map_err(Error::GitError),
-            Self::Branchless {
+            Self::Shorthand {
                 provider,
                 owner,
                 repo,
-            } => RepoBuilder::new()
-                .fetch_options(options)
-                .clone(&provider_link(provider, owner, repo)?, path)
-                .map_err(Error::GitError),
-            Self::Other { url } => RepoBuilder::new()
-                .fetch_options(options)
-                .clone(&url, path)
-                .map_err(Error::GitError),
+                ..
+            } => provider_link(provider, owner, repo, custom),
+            Self::Other { url, .. } => Ok(url.to_owned()),
+        }
+    }
+
+    /// Branch, tag or commit given within url
+    pub fn reference(&self) -> Option<String> {
+        match self {
+            Self::Shorthand { reference, .. } | Self::Other { reference, .. } => reference.clone(),
         }
     }
 }
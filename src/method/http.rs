@@ -1,20 +1,28 @@
 use crate::{
+    config::{AuthConfig, Credential, HostAuth, HttpConfig},
     error::{BleurError, Result},
-    method::Fetchable,
+    method::{archive::Archive, verify::Verification, Fetchable},
 };
-use reqwest::blocking::{Client, ClientBuilder};
-use std::{
-    fs,
-    io::{self, Write},
+use indicatif::{ProgressBar, ProgressStyle};
+use reqwest::{
+    blocking::{Client, ClientBuilder, RequestBuilder, Response},
+    header::{CONTENT_LENGTH, CONTENT_TYPE},
 };
-use std::{fs::File, path::PathBuf};
+use std::io::{Read, Write};
+use std::{fs::File, path::PathBuf, thread, time::Duration};
 use url::Url;
 
+/// Size of chunks archive is written to disk with
+const CHUNK: usize = 64 * 1024;
+
 #[derive(Debug)]
 pub struct Http {
     url: Url,
     path: PathBuf,
     client: Client,
+    settings: HttpConfig,
+    verification: Verification,
+    auth: AuthConfig,
 }
 
 impl Http {
@@ -23,68 +31,201 @@ impl Http {
             url,
             path,
             client: ClientBuilder::new().build().unwrap_or_default(),
+            settings: HttpConfig::default(),
+            verification: Verification::default(),
+            auth: AuthConfig::default(),
         }
     }
 
-    pub fn download(&self) -> Result<PathBuf> {
-        let res = self
-            .client
-            .get(self.url.clone())
-            .send()
-            .map_err(BleurError::CantDownloadViaHttp)?;
+    pub fn with_settings(self, settings: HttpConfig) -> Self {
+        Self {
+            client: ClientBuilder::new()
+                .connect_timeout(Duration::from_secs(settings.connect_timeout))
+                .timeout(Duration::from_secs(settings.timeout))
+                .build()
+                .unwrap_or_default(),
+            settings,
+            ..self
+        }
+    }
 
-        let path = self
-            .path
-            .join(self.url.path().split('/').next_back().unwrap());
+    /// Credentials per host, ones inside of url go there too
+    pub fn with_auth(self, auth: &AuthConfig) -> Self {
+        let mut auth = auth.clone();
+        let mut url = self.url;
+
+        if let (Some(host), Some(password)) = (url.host_str(), url.password()) {
+            auth.hosts.insert(
+                host.to_owned(),
+                HostAuth {
+                    username: Some(url.username().to_owned()),
+                    password: Some(password.to_owned()),
+                    ..Default::default()
+                },
+            );
+
+            // Keep secrets out of urls, as urls end up in errors
+            let _ = url.set_username("");
+            let _ = url.set_password(None);
+        }
 
-        let mut file = File::create(&path)
-            .map_err(|_| BleurError::CantCreateFile(self.path.to_string_lossy().to_string()))?;
-        file.write_all(&res.bytes().map_err(BleurError::CantDownloadViaHttp)?)
-            .map_err(|_| BleurError::CantWriteToFile)?;
+        Self { url, auth, ..self }
+    }
+
+    /// Request with credentials of its host attached
+    fn get(&self, url: Url) -> RequestBuilder {
+        let request = self.client.get(url.clone());
 
-        Ok(path)
+        match url.host_str().and_then(|h| self.auth.credential(h)) {
+            Some(Credential::Basic(user, password)) => request.basic_auth(user, Some(password)),
+            Some(Credential::Bearer(token)) => request.bearer_auth(token),
+            None => request,
+        }
     }
 
-    pub fn unarchive(&self, path: &PathBuf) -> Result<()> {
-        let file = fs::File::open(path).unwrap();
-        let mut archive = zip::ZipArchive::new(file).unwrap();
-
-        for i in 0..archive.len() {
-            let mut file = archive.by_index(i).unwrap();
-
-            let outpath = match file.enclosed_name() {
-                Some(path) => path,
-                None => continue,
-            };
-
-            // Skip base folder, no need.
-            let mut outpath = outpath.components();
-            outpath.next();
-            let outpath = self.path.join(outpath.as_path());
-
-            if file.is_dir() {
-                fs::create_dir_all(&outpath).unwrap();
-            } else {
-                if let Some(p) = outpath.parent() {
-                    if !p.exists() {
-                        fs::create_dir_all(p).unwrap();
-                    }
+    pub fn with_verification(self, verification: Verification) -> Self {
+        Self {
+            verification,
+            ..self
+        }
+    }
+
+    /// Signature text, either downloaded or read from disk
+    pub fn signature(&self) -> Result<Option<String>> {
+        let Some(location) = self.verification.signature.as_ref() else {
+            return Ok(None);
+        };
+
+        match Url::parse(location) {
+            Ok(url) if url.scheme() == "http" || url.scheme() == "https" => self
+                .get(url)
+                .send()
+                .and_then(|r| r.error_for_status())
+                .and_then(|r| r.text())
+                .map_err(|e| BleurError::CantReadSignature(e.to_string())),
+            _ => std::fs::read_to_string(location)
+                .map_err(|e| BleurError::CantReadSignature(e.to_string())),
+        }
+        .map(Some)
+    }
+
+    /// Send request, retrying on network and server failures
+    fn send(&self) -> Result<Response> {
+        let mut attempt = 0;
+
+        loop {
+            match self
+                .get(self.url.clone())
+                .send()
+                .and_then(|r| r.error_for_status())
+            {
+                Ok(res) => return Ok(res),
+                Err(e)
+                    if attempt < self.settings.retries
+                        && (e.is_connect()
+                            || e.is_timeout()
+                            || e.status().is_some_and(|s| s.is_server_error())) =>
+                {
+                    attempt += 1;
+                    thread::sleep(Duration::from_secs(1 << attempt));
                 }
-                let mut outfile = fs::File::create(&outpath).unwrap();
-                io::copy(&mut file, &mut outfile).unwrap();
+                Err(e) => return Err(BleurError::CantDownloadViaHttp(e)),
             }
+        }
+    }
 
-            // Get and Set permissions
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
+    pub fn download(&self) -> Result<(PathBuf, Option<Archive>)> {
+        let mut res = self.send()?;
+        // Absurdly large limits just mean no limit at all
+        let limit = self.settings.max_size.saturating_mul(1024 * 1024);
+
+        let name = self
+            .url
+            .path()
+            .split('/')
+            .next_back()
+            .filter(|n| !n.is_empty())
+            .unwrap_or("archive")
+            .to_owned();
+
+        // Content-Type goes first, then the name of file
+        let archive = res
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|c| c.to_str().ok())
+            .and_then(Archive::from_content_type)
+            .or_else(|| Archive::from_name(&name));
+
+        let length = res
+            .headers()
+            .get(CONTENT_LENGTH)
+            .map(|l| {
+                l.to_str()
+                    .ok()
+                    .and_then(|l| l.parse::<u64>().ok())
+                    .ok_or(BleurError::CantGetContentLength)
+            })
+            .transpose()?;
+
+        // No need to download what's going to be thrown away
+        if length.is_some_and(|l| l > limit) {
+            return Err(BleurError::ArchiveTooLarge(self.settings.max_size));
+        }
 
-                if let Some(mode) = file.unix_mode() {
-                    fs::set_permissions(&outpath, fs::Permissions::from_mode(mode)).unwrap();
-                }
+        let progress = match length {
+            Some(l) => ProgressBar::new(l).with_style(
+                ProgressStyle::with_template("{bar:40} {bytes}/{total_bytes} ({eta})")
+                    .unwrap_or_else(|_| ProgressStyle::default_bar()),
+            ),
+            None => ProgressBar::new_spinner().with_style(
+                ProgressStyle::with_template("{spinner} {bytes}")
+                    .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+            ),
+        };
+
+        let path = self.path.join(name);
+
+        let mut file = File::create(&path)
+            .map_err(|_| BleurError::CantCreateFile(self.path.to_string_lossy().to_string()))?;
+
+        let mut buffer = vec![0; CHUNK];
+        let mut total = 0;
+
+        loop {
+            let read = res
+                .read(&mut buffer)
+                .map_err(|e| BleurError::CantReadResponse(e.to_string()))?;
+
+            if read == 0 {
+                break;
             }
+
+            // Content-Length might be missing or lying
+            total += read as u64;
+            if total > limit {
+                progress.abandon();
+                return Err(BleurError::ArchiveTooLarge(self.settings.max_size));
+            }
+
+            file.write_all(&buffer[..read])
+                .map_err(|_| BleurError::CantWriteToFile)?;
+            progress.inc(read as u64);
         }
 
+        progress.finish_and_clear();
+
+        Ok((path, archive))
+    }
+
+    pub fn unarchive(&self, path: &PathBuf, archive: Option<Archive>) -> Result<()> {
+        // Nothing else worked, so peek at the file itself
+        archive
+            .or_else(|| Archive::from_magic(path))
+            .ok_or(BleurError::UnknownArchiveFormat(
+                path.to_string_lossy().to_string(),
+            ))?
+            .extract(path, &self.path)?;
+
         std::fs::remove_file(path).map_err(|_| BleurError::CantDeleteOldArchive)?;
 
         Ok(())
@@ -92,14 +233,20 @@ impl Http {
 }
 
 impl Fetchable for Http {
-    fn fetch(&self) -> Result<()> {
+    fn fetch(&self) -> Result<Option<String>> {
         // Download the archive
-        let file = self.download()?;
+        let (file, archive) = self.download()?;
+
+        // Verify before a single entry gets extracted
+        if !self.verification.is_empty() {
+            self.verification
+                .check(&file, self.signature()?.as_deref())?;
+        }
 
         // Unarchive and then delete archive
-        self.unarchive(&file)?;
+        self.unarchive(&file, archive)?;
 
-        // Cooked
-        Ok(())
+        // Cooked, archives have no revision to speak of
+        Ok(None)
     }
 }
@@ -0,0 +1,111 @@
+use crate::{
+    error::{BleurError, Result},
+    method::Fetchable,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use url::Url;
+use walkdir::WalkDir;
+
+#[derive(Debug)]
+pub struct Local {
+    url: Url,
+    path: PathBuf,
+}
+
+impl Local {
+    pub fn new(url: Url, path: PathBuf) -> Self {
+        Self { url, path }
+    }
+
+    pub fn copy(&self) -> Result<()> {
+        let source = self
+            .url
+            .to_file_path()
+            .map_err(|_| BleurError::InvalidFilePath(PathBuf::from(self.url.path())))?;
+
+        if !source.is_dir() {
+            return Err(BleurError::InvalidFilePath(source));
+        }
+
+        copy_tree(&source, &self.path)
+    }
+}
+
+/// Copy directory contents without repository internals, making
+/// sure copies are writable, as sources like nix store are not
+pub fn copy_tree(source: &Path, destination: &Path) -> Result<()> {
+    // Same as with cloning, repository internals are not needed
+    for entry in WalkDir::new(source)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+    {
+        let entry = entry.map_err(|e| BleurError::IOError(e.into()))?;
+        let outpath = destination.join(
+            entry
+                .path()
+                .strip_prefix(source)
+                .map_err(|_| BleurError::InvalidFilePath(entry.path().to_path_buf()))?,
+        );
+
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&outpath)?;
+            continue;
+        }
+
+        if entry.file_type().is_symlink() {
+            link(entry.path(), &outpath)?;
+            continue;
+        }
+
+        fs::copy(entry.path(), &outpath)?;
+
+        let mut permissions = fs::metadata(&outpath)?.permissions();
+        if permissions.readonly() {
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+
+                permissions.set_mode(permissions.mode() | 0o200);
+            }
+
+            #[cfg(not(unix))]
+            permissions.set_readonly(false);
+
+            fs::set_permissions(&outpath, permissions)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Recreate symlink as it is
+#[cfg(unix)]
+pub fn link(origin: &Path, target: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(origin)?, target)?;
+
+    Ok(())
+}
+
+/// Copy what symlink points to, as links can't be
+/// made the same way everywhere
+#[cfg(not(unix))]
+pub fn link(origin: &Path, target: &Path) -> Result<()> {
+    if origin.is_dir() {
+        fs::create_dir_all(target)?;
+        return copy_tree(origin, target);
+    }
+
+    fs::copy(origin, target)?;
+
+    Ok(())
+}
+
+impl Fetchable for Local {
+    fn fetch(&self) -> Result<Option<String>> {
+        self.copy().map(|_| None)
+    }
+}
@@ -1,15 +1,20 @@
+pub mod archive;
 pub mod git;
 pub mod http;
+pub mod local;
+pub mod nix;
+pub mod verify;
 
 use crate::{
-    method::{git::Git, http::Http},
+    method::{git::Git, http::Http, local::Local, nix::Nix},
     Result,
 };
 use std::path::PathBuf;
 use url::Url;
 
 pub trait Fetchable {
-    fn fetch(&self) -> Result<()>;
+    /// Fetch source, returning its resolved revision if it has any
+    fn fetch(&self) -> Result<Option<String>>;
 }
 
 pub trait Methodical {
@@ -20,15 +25,29 @@ pub trait Methodical {
 pub enum Method {
     Git(Git),
     Http(Http),
+    Local(Local),
+    Nix(Nix),
+}
+
+impl Method {
+    /// Name of method, as in --method
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Git(_) => "git",
+            Self::Http(_) => "http",
+            Self::Local(_) => "local",
+            Self::Nix(_) => "nix",
+        }
+    }
 }
 
 impl Fetchable for Method {
-    fn fetch(&self) -> Result<()> {
+    fn fetch(&self) -> Result<Option<String>> {
         match &self {
             Self::Http(h) => h.fetch(),
             Self::Git(g) => g.fetch(),
-        }?;
-
-        Ok(())
+            Self::Local(l) => l.fetch(),
+            Self::Nix(n) => n.fetch(),
+        }
     }
 }
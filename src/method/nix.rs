@@ -0,0 +1,172 @@
+use crate::{
+    error::{BleurError, Result},
+    method::{local::copy_tree, Fetchable},
+};
+use std::{path::PathBuf, process::Command};
+use url::Url;
+
+/// Flake output templates live under, same as `nix flake init`
+static TEMPLATES: &str = "templates";
+
+#[derive(Debug)]
+pub struct Nix {
+    url: Url,
+    path: PathBuf,
+}
+
+impl Nix {
+    pub fn new(url: Url, path: PathBuf) -> Self {
+        Self { url, path }
+    }
+
+    /// Flake reference and attribute of template, #rust and
+    /// #templates.rust are the same, nothing means default
+    fn reference(&self) -> (String, String) {
+        let mut flake = self.url.clone();
+        flake.set_fragment(None);
+
+        let attribute = match self.url.fragment().filter(|f| !f.is_empty()) {
+            Some(f) if f.starts_with(&format!("{TEMPLATES}.")) => f.to_owned(),
+            Some(f) => format!("{TEMPLATES}.{f}"),
+            None => format!("{TEMPLATES}.default"),
+        };
+
+        (flake.to_string(), attribute)
+    }
+
+    /// Store path of template, nix copies it to store on its own
+    pub fn evaluate(&self) -> Result<PathBuf> {
+        let nix = which::which("nix").map_err(|_| BleurError::NoToolForInit)?;
+        let (flake, attribute) = self.reference();
+
+        let output = Command::new(nix)
+            .args(["--extra-experimental-features", "nix-command flakes"])
+            .args(["eval", "--raw"])
+            .arg(format!("{flake}#{attribute}.path"))
+            .output()?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+
+            // Wording differs between versions of nix
+            if error.contains("does not provide attribute") || error.contains("missing") {
+                return Err(BleurError::NoSuchFlakeTemplate(attribute));
+            }
+
+            return Err(BleurError::CommandExecutionFail);
+        }
+
+        let path = PathBuf::from(String::from_utf8(output.stdout)?.trim());
+
+        path.is_dir()
+            .then_some(path.clone())
+            .ok_or(BleurError::InvalidFilePath(path))
+    }
+}
+
+impl Fetchable for Nix {
+    fn fetch(&self) -> Result<Option<String>> {
+        let store = self.evaluate()?;
+
+        copy_tree(&store, &self.path)?;
+
+        // Store path pins exact contents, like a commit would
+        Ok(Some(store.to_string_lossy().to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::Path};
+    use tempfile::{tempdir, TempDir};
+
+    /// Local `path:` flake with `rust` template, so nothing is fetched
+    fn flake() -> TempDir {
+        let flake = tempdir().unwrap();
+        let template = flake.path().join("rust");
+
+        fs::create_dir(&template).unwrap();
+        fs::write(template.join("bleur.toml"), "[project]\nname = \"rust\"\n").unwrap();
+        fs::write(
+            flake.path().join("flake.nix"),
+            r#"{
+              outputs = { self }: {
+                templates.rust = {
+                  path = ./rust;
+                  description = "Rust template";
+                };
+              };
+            }"#,
+        )
+        .unwrap();
+
+        flake
+    }
+
+    fn nix(flake: &Path, fragment: &str, path: PathBuf) -> Nix {
+        let url = Url::parse(&format!("path:{}#{fragment}", flake.display())).unwrap();
+
+        Nix::new(url, path)
+    }
+
+    /// Evaluation needs nix itself, which isn't everywhere
+    fn available() -> bool {
+        let found = which::which("nix").is_ok();
+
+        if !found {
+            eprintln!("nix is not on PATH, skipping");
+        }
+
+        found
+    }
+
+    #[test]
+    fn resolves_template_attribute() {
+        let path = PathBuf::from("/nowhere");
+        let flake = Path::new("/flake");
+
+        for (fragment, attribute) in [
+            ("rust", "templates.rust"),
+            ("templates.rust", "templates.rust"),
+            ("", "templates.default"),
+        ] {
+            let (url, found) = nix(flake, fragment, path.clone()).reference();
+
+            assert_eq!(url, "path:/flake");
+            assert_eq!(found, attribute);
+        }
+    }
+
+    #[test]
+    fn fetches_template_from_store() {
+        if !available() {
+            return;
+        }
+
+        let flake = flake();
+        let destination = tempdir().unwrap();
+        let revision = nix(flake.path(), "rust", destination.path().to_path_buf())
+            .fetch()
+            .unwrap();
+
+        assert!(revision.is_some_and(|r| r.starts_with("/nix/store/")));
+        assert!(destination.path().join("bleur.toml").is_file());
+    }
+
+    #[test]
+    fn refuses_missing_template() {
+        if !available() {
+            return;
+        }
+
+        let flake = flake();
+        let destination = tempdir().unwrap();
+        let result = nix(flake.path(), "python", destination.path().to_path_buf()).fetch();
+
+        assert!(matches!(
+            result,
+            Err(BleurError::NoSuchFlakeTemplate(a)) if a == "templates.python"
+        ));
+    }
+}
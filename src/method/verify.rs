@@ -0,0 +1,127 @@
+use crate::{
+    config::TrustedKeys,
+    error::{BleurError, Result},
+};
+use sha2::{Digest, Sha256};
+use std::{fs, path::Path};
+use walkdir::WalkDir;
+
+/// Namespace used by `ssh-keygen -Y sign -n file`
+static SSH_NAMESPACE: &str = "file";
+
+/// What fetched archive must satisfy before being extracted
+#[derive(Debug, Default, Clone)]
+pub struct Verification {
+    /// Expected sha256 of archive in hex
+    pub sha256: Option<String>,
+
+    /// Path or url to minisign or ssh signature of archive
+    pub signature: Option<String>,
+
+    /// Keys which signature may be made with
+    pub trusted: TrustedKeys,
+}
+
+impl Verification {
+    pub fn is_empty(&self) -> bool {
+        self.sha256.is_none() && self.signature.is_none()
+    }
+
+    /// Check archive bytes against checksum and signature text
+    pub fn check(&self, archive: &Path, signature: Option<&str>) -> Result<()> {
+        let bytes = fs::read(archive)?;
+
+        if let Some(expected) = self.sha256.as_ref() {
+            checksum(expected, &digest(&bytes))?;
+        }
+
+        match signature {
+            Some(signature) => self.signed(&bytes, signature),
+            None => Ok(()),
+        }
+    }
+
+    fn signed(&self, bytes: &[u8], signature: &str) -> Result<()> {
+        if signature
+            .trim_start()
+            .starts_with("-----BEGIN SSH SIGNATURE-----")
+        {
+            let signature = ssh_key::SshSig::from_pem(signature)
+                .map_err(|e| BleurError::InvalidSignature(e.to_string()))?;
+
+            if self.trusted.ssh.is_empty() {
+                return Err(BleurError::NoTrustedKeys);
+            }
+
+            return self
+                .trusted
+                .ssh
+                .iter()
+                .filter_map(|k| ssh_key::PublicKey::from_openssh(k).ok())
+                .any(|k| k.verify(SSH_NAMESPACE, bytes, &signature).is_ok())
+                .then_some(())
+                .ok_or(BleurError::SignatureNotTrusted);
+        }
+
+        let signature = minisign_verify::Signature::decode(signature)
+            .map_err(|e| BleurError::InvalidSignature(e.to_string()))?;
+
+        if self.trusted.minisign.is_empty() {
+            return Err(BleurError::NoTrustedKeys);
+        }
+
+        self.trusted
+            .minisign
+            .iter()
+            .filter_map(|k| minisign_verify::PublicKey::from_base64(k.trim()).ok())
+            .any(|k| k.verify(bytes, &signature, false).is_ok())
+            .then_some(())
+            .ok_or(BleurError::SignatureNotTrusted)
+    }
+}
+
+/// Compare hex digests, case doesn't matter
+pub fn checksum(expected: &str, actual: &str) -> Result<()> {
+    let expected = expected.trim().to_lowercase();
+
+    if expected.len() != 64 || !expected.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(BleurError::InvalidChecksum(expected));
+    }
+
+    (expected == actual)
+        .then_some(())
+        .ok_or(BleurError::ChecksumMismatch(expected, actual.to_owned()))
+}
+
+/// Hex sha256 of archive bytes
+pub fn digest(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Digest of a directory, reproducible with `bleur digest <dir>`.
+/// It's sha256 over every regular file, walking depth first with
+/// siblings sorted by name, each fed as `path NUL contents NUL` where
+/// path is relative with `/` separators. Symlinks are not counted.
+pub fn tree_digest(path: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+
+    for entry in WalkDir::new(path).sort_by_file_name() {
+        let entry = entry.map_err(|e| BleurError::IOError(e.into()))?;
+
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let relative = entry
+            .path()
+            .strip_prefix(path)
+            .map_err(|_| BleurError::InvalidFilePath(entry.path().to_path_buf()))?;
+
+        hasher.update(relative.to_string_lossy().replace('\\', "/").as_bytes());
+        hasher.update([0]);
+        hasher.update(fs::read(entry.path())?);
+        hasher.update([0]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
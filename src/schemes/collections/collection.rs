@@ -1,3 +1,5 @@
+use crate::{method::verify, Result};
+use owo_colors::OwoColorize;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -6,6 +8,11 @@ pub struct Collection {
     path: String,
     description: String,
     welcome: String,
+
+    /// Pinned digest of template directory contents, which is
+    /// only as trustworthy as collection file carrying it
+    #[serde(default)]
+    sha256: Option<String>,
 }
 
 impl Collection {
@@ -14,4 +21,23 @@ impl Collection {
 
         base.join(addition)
     }
+
+    /// Make sure template directory is exactly the pinned one. Pin
+    /// means nothing if whoever changed template could change it too,
+    /// so collection itself must come verified or from local disk,
+    /// otherwise pin is ignored with a warning.
+    pub fn verify(&self, base: PathBuf, trusted: bool) -> Result<()> {
+        match self.sha256.as_ref() {
+            Some(_) if !trusted => {
+                eprintln!(
+                    "{} ignoring pin of template {} as collection itself isn't verified",
+                    "warning:".yellow(),
+                    self.path
+                );
+                Ok(())
+            }
+            Some(expected) => verify::checksum(expected, &verify::tree_digest(&self.path(base))?),
+            None => Ok(()),
+        }
+    }
 }
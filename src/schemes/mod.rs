@@ -12,7 +12,7 @@ static MAX_COLLECTIONS_DEPTH: u8 = 5;
 #[derive(Debug, Default, Clone)]
 pub enum Configuration {
     // If repo is a single template
-    Template(Template),
+    Template(Box<Template>),
 
     // If repo contains collection of templates
     Collections(Collections),
@@ -34,7 +34,7 @@ impl Configuration {
         if let Some(text) = config {
             // And if it's parsible to Template type
             if let Ok(t) = toml::from_str::<Template>(&text) {
-                return Configuration::Template(t.with_path(path));
+                return Configuration::Template(Box::new(t.with_path(path)));
             }
 
             // And if it's parsible to Collection type
@@ -47,7 +47,17 @@ impl Configuration {
         Self::Empty
     }
 
-    pub fn surely_template(path: PathBuf, depth: u8) -> Result<Self> {
+    /// Walk down collections until a template is found. Collection
+    /// entries are picked by `name` (slash separated for nested
+    /// collections) or asked from user when it's allowed. Pins of
+    /// entries are honored only if collection itself is `trusted`.
+    pub fn surely_template(
+        path: PathBuf,
+        depth: u8,
+        name: Option<&str>,
+        interactive: bool,
+        trusted: bool,
+    ) -> Result<Self> {
         use Configuration::*;
         depth
             .gt(&MAX_COLLECTIONS_DEPTH)
@@ -59,20 +69,36 @@ impl Configuration {
             .and_then(|c| match c {
                 Template(t) => Ok(Self::Template(t)),
                 Empty => Err(Error::NoTemplateConfiguration),
-                Collections(c) => inquire::Select::new(
-                    "Choose the template you would like to bootstrap:",
-                    c.keys(),
-                )
-                .prompt()
-                .map_err(Error::CantParseUserPrompt)
-                .and_then(|s| c.select(s).ok_or(Error::NoSuchTemplateInCollection))
-                .and_then(|c| Self::surely_template(c.path(path), depth + 1)),
+                Collections(c) => {
+                    let (current, rest) = match name.map(|n| n.split_once('/').unwrap_or((n, ""))) {
+                        Some((current, rest)) => {
+                            (Some(current), Some(rest).filter(|r| !r.is_empty()))
+                        }
+                        None => (None, None),
+                    };
+
+                    match current {
+                        Some(current) => Ok(current.to_owned()),
+                        None if interactive => inquire::Select::new(
+                            "Choose the template you would like to bootstrap:",
+                            c.keys(),
+                        )
+                        .prompt()
+                        .map_err(Error::CantParseUserPrompt),
+                        None => Err(Error::TemplateNameRequired(c.keys().join(", "))),
+                    }
+                    .and_then(|s| c.select(s).ok_or(Error::NoSuchTemplateInCollection))
+                    .and_then(|c| c.verify(path.clone(), trusted).map(|_| c))
+                    .and_then(|c| {
+                        Self::surely_template(c.path(path), depth + 1, rest, interactive, trusted)
+                    })
+                }
             })
     }
 
     pub fn template(self) -> Result<Template> {
         match self {
-            Configuration::Template(template) => Ok(template),
+            Configuration::Template(template) => Ok(*template),
             Configuration::Empty => Err(Error::TemplateIsInvalid),
             Configuration::Collections(_) => Err(Error::TemplateIsInvalid),
         }
@@ -1,3 +1,6 @@
+use crate::{Error, Result};
+use regex::Regex;
+
 enum Instructions {
     /// Make whole value uppercase
     Uppercase,
@@ -8,52 +11,277 @@ enum Instructions {
     /// Replace chars in string
     ReplaceAll(String, String),
 
-    /// If the function name is not recognized
-    Unknown,
-}
-
-impl<T: AsRef<str>> From<T> for Instructions {
-    fn from(value: T) -> Self {
-        match value.as_ref() {
-            s if s.eq_ignore_ascii_case("uppercase") => Self::Uppercase,
-            s if s.eq_ignore_ascii_case("lowercase") => Self::Lowercase,
-            value => {
-                if let Some((name, args)) = value.split_once(':') {
-                    if name.eq_ignore_ascii_case("replaceAll") {
-                        if let Some((from, to)) = args.split_once("->") {
-                            return Self::ReplaceAll(from.into(), to.into());
-                        }
-                    }
-                }
+    /// Words joined with underscores: my_project
+    SnakeCase,
+
+    /// Words joined with dashes: my-project
+    KebabCase,
+
+    /// Words joined, all but first capitalized: myProject
+    CamelCase,
+
+    /// Words joined, all capitalized: MyProject
+    PascalCase,
+
+    /// Uppercase words joined with underscores: MY_PROJECT
+    ScreamingSnake,
+
+    /// Capitalized words joined with spaces: My Project
+    Title,
+
+    /// Strip surrounding whitespace
+    Trim,
+
+    /// Put text before value
+    Prefix(String),
+
+    /// Put text after value
+    Suffix(String),
+
+    /// Lowercase alphanumerics joined with dashes, fit for urls
+    Slugify,
+
+    /// Keep only first N characters
+    Truncate(usize),
+
+    /// Use text if value is empty
+    Default(String),
+
+    /// Replace regex matches, captures are available as $1
+    RegexReplace(Regex, String),
+}
+
+impl Instructions {
+    fn parse(value: &str) -> Result<Self> {
+        let unknown = || Error::UnknownApplyFunction(value.to_owned());
+
+        let (name, args) = match value.split_once(':') {
+            Some((name, args)) => (name.trim(), Some(args)),
+            None => (value.trim(), None),
+        };
 
-                Self::Unknown
+        let is = |expected: &str| name.eq_ignore_ascii_case(expected);
+
+        Ok(match args {
+            None if is("uppercase") => Self::Uppercase,
+            None if is("lowercase") => Self::Lowercase,
+            // Short names read better inside of @name|kebab@
+            None if is("snake_case") || is("snake") => Self::SnakeCase,
+            None if is("kebab-case") || is("kebab") => Self::KebabCase,
+            None if is("camelCase") || is("camel") => Self::CamelCase,
+            None if is("PascalCase") || is("pascal") => Self::PascalCase,
+            None if is("SCREAMING_SNAKE") || is("screaming") => Self::ScreamingSnake,
+            None if is("title") => Self::Title,
+            None if is("trim") => Self::Trim,
+            None if is("slugify") => Self::Slugify,
+            Some(args) if is("replaceAll") => args
+                .split_once("->")
+                .map(|(from, to)| Self::ReplaceAll(from.into(), to.into()))
+                .ok_or_else(unknown)?,
+            Some(args) if is("prefix") => Self::Prefix(args.into()),
+            Some(args) if is("suffix") => Self::Suffix(args.into()),
+            Some(args) if is("default") => Self::Default(args.into()),
+            Some(args) if is("truncate") => {
+                Self::Truncate(args.trim().parse().map_err(|_| unknown())?)
+            }
+            Some(args) if is("regexReplace") => {
+                let (pattern, to) = args.split_once("->").ok_or_else(unknown)?;
+                Self::RegexReplace(Regex::new(pattern)?, to.into())
+            }
+            _ => return Err(unknown()),
+        })
+    }
+
+    fn execute(&self, current: String) -> String {
+        match self {
+            Self::Uppercase => current.to_uppercase(),
+            Self::Lowercase => current.to_lowercase(),
+            Self::ReplaceAll(from, to) => current.replace(from, to),
+            Self::SnakeCase => join(&current, "_", str::to_lowercase),
+            Self::KebabCase => join(&current, "-", str::to_lowercase),
+            Self::CamelCase => {
+                let pascal = join(&current, "", capitalize);
+                let mut chars = pascal.chars();
+
+                chars
+                    .next()
+                    .map(|c| c.to_lowercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+            Self::PascalCase => join(&current, "", capitalize),
+            Self::ScreamingSnake => join(&current, "_", str::to_uppercase),
+            Self::Title => join(&current, " ", capitalize),
+            Self::Trim => current.trim().to_owned(),
+            Self::Prefix(prefix) => format!("{prefix}{current}"),
+            Self::Suffix(suffix) => format!("{current}{suffix}"),
+            Self::Slugify => current
+                .to_lowercase()
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|w| !w.is_empty())
+                .collect::<Vec<_>>()
+                .join("-"),
+            Self::Truncate(length) => current.chars().take(*length).collect(),
+            Self::Default(default) if current.is_empty() => default.to_owned(),
+            Self::Default(_) => current,
+            Self::RegexReplace(regex, to) => regex.replace_all(&current, to.as_str()).into(),
+        }
+    }
+}
+
+/// Split value into words on separators and case changes,
+/// so "myHTTPServer_v2" becomes "my", "HTTP", "Server", "v2"
+fn words(value: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let chars: Vec<char> = value.chars().collect();
+
+    for (i, c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
             }
+            continue;
+        }
+
+        let previous = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1);
+
+        let boundary = c.is_uppercase()
+            && previous.is_some_and(|p| {
+                p.is_lowercase()
+                    || p.is_numeric()
+                    || (p.is_uppercase() && next.is_some_and(|n| n.is_lowercase()))
+            });
+
+        if boundary && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
         }
+
+        current.push(*c);
+    }
+
+    if !current.is_empty() {
+        words.push(current);
     }
+
+    words
+}
+
+/// Whether piece looks like a function, even a misspelled one
+fn named(piece: &str) -> bool {
+    let name = piece.split_once(':').map_or(piece, |(n, _)| n).trim();
+
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Whether piece is a function taking free-form text to replace
+fn replacing(piece: &str) -> bool {
+    piece.split_once(':').is_some_and(|(n, _)| {
+        ["replaceAll", "regexReplace"]
+            .iter()
+            .any(|r| n.trim().eq_ignore_ascii_case(r))
+    })
+}
+
+fn join(value: &str, separator: &str, case: fn(&str) -> String) -> String {
+    words(value)
+        .iter()
+        .map(|w| case(w))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+
+    chars
+        .next()
+        .map(|c| {
+            c.to_uppercase()
+                .chain(chars.as_str().to_lowercase().chars())
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
 pub struct Apply(Vec<Instructions>);
 
 impl Apply {
-    pub fn parse<T: ToString>(input: T) -> Apply {
-        Apply(
-            input
-                .to_string()
-                .split(",")
-                .map(Instructions::from)
-                .collect::<Vec<Instructions>>(),
-        )
+    /// Parse comma separated functions, refusing unknown ones
+    pub fn parse<T: AsRef<str>>(input: T) -> Result<Apply> {
+        Self::split(input.as_ref(), ',')
+    }
+
+    /// Parse pipe separated functions of `@name|snake_case|prefix:x@`
+    pub fn filters<T: AsRef<str>>(input: T) -> Result<Apply> {
+        Self::split(input.as_ref(), '|')
+    }
+
+    /// Separator is a part of previous arguments, like `{1,3}` of regex,
+    /// if what follows isn't a known function and either previous one
+    /// takes free-form text or what follows can't be a function name
+    fn split(input: &str, separator: char) -> Result<Apply> {
+        let mut pieces: Vec<String> = Vec::new();
+
+        for piece in input.split(separator) {
+            match pieces.last_mut() {
+                Some(last)
+                    if last.contains(':')
+                        && (replacing(last) || !named(piece))
+                        && Instructions::parse(piece).is_err() =>
+                {
+                    last.push(separator);
+                    last.push_str(piece);
+                }
+                _ => pieces.push(piece.to_owned()),
+            }
+        }
+
+        pieces
+            .iter()
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| Instructions::parse(s))
+            .collect::<Result<Vec<Instructions>>>()
+            .map(Apply)
+    }
+
+    /// Whether there's a fallback for empty value
+    pub fn has_default(&self) -> bool {
+        self.0.iter().any(|i| matches!(i, Instructions::Default(_)))
     }
 
     pub fn execute<T: ToString>(&self, input: T) -> String {
-        self.0.iter().fold(
-            input.to_string(),
-            |current, instruction| match instruction {
-                Instructions::Uppercase => current.to_uppercase(),
-                Instructions::Lowercase => current.to_lowercase(),
-                Instructions::ReplaceAll(from, to) => current.replace(from, to),
-                Instructions::Unknown => current,
-            },
-        )
+        self.0
+            .iter()
+            .fold(input.to_string(), |current, instruction| {
+                instruction.execute(current)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_separators_inside_of_arguments() {
+        let apply = Apply::parse(r"regexReplace:^(\d{1,3})-x$->n$1,uppercase").unwrap();
+        assert_eq!(apply.execute("12-x"), "N12");
+
+        let apply = Apply::parse("replaceAll:a,b->c, d,prefix:x").unwrap();
+        assert_eq!(apply.execute("a,b"), "xc, d");
+
+        let apply = Apply::filters("regexReplace:a|b->c|kebab").unwrap();
+        assert_eq!(apply.execute("ab Aa"), "cc-ac");
+    }
+
+    #[test]
+    fn refuses_unknown_functions() {
+        assert!(Apply::parse("uppercase,shout").is_err());
+        assert!(Apply::parse("prefix:x,snake_cse").is_err());
+        assert!(Apply::filters("default:x|kebap").is_err());
     }
 }
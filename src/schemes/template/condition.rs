@@ -0,0 +1,312 @@
+use crate::{
+    manager::Glubtastic,
+    value::{Globals, Value},
+    Error, Result,
+};
+
+/// Parsed `when` expression like `@docker@ == 'yes' && !@minimal@`
+/// or `serde in @features@`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    /// Quoted text or bare word
+    Literal(String),
+
+    /// Reference to a global @variable@
+    Variable(String),
+
+    Equal(Box<Condition>, Box<Condition>),
+    NotEqual(Box<Condition>, Box<Condition>),
+
+    /// Item is one of choices of multiselect or comma separated values
+    In(Box<Condition>, Box<Condition>),
+
+    Not(Box<Condition>),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Text(String),
+    Variable(String),
+    Equal,
+    NotEqual,
+    Not,
+    And,
+    Or,
+    Open,
+    Close,
+}
+
+/// Values which count as false when used on their own
+fn truthy(value: &str) -> bool {
+    !matches!(
+        value.trim().to_lowercase().as_str(),
+        "" | "false" | "no" | "n" | "0" | "off"
+    )
+}
+
+fn tokenize(input: &str) -> std::result::Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '=' if chars.next_if_eq(&'=').is_some() => tokens.push(Token::Equal),
+            '!' if chars.next_if_eq(&'=').is_some() => tokens.push(Token::NotEqual),
+            '!' => tokens.push(Token::Not),
+            '&' if chars.next_if_eq(&'&').is_some() => tokens.push(Token::And),
+            '|' if chars.next_if_eq(&'|').is_some() => tokens.push(Token::Or),
+            '\'' | '"' => {
+                let text = until(&mut chars, c).ok_or("unterminated quote")?;
+                tokens.push(Token::Text(text));
+            }
+            '@' => {
+                let name = until(&mut chars, '@').ok_or("unterminated variable reference")?;
+                if name.is_empty() {
+                    return Err("empty variable reference".into());
+                }
+                tokens.push(Token::Variable(name));
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '-' || c == '.' => {
+                let mut word = String::from(c);
+                while let Some(n) =
+                    chars.next_if(|n| n.is_alphanumeric() || *n == '_' || *n == '-' || *n == '.')
+                {
+                    word.push(n);
+                }
+                tokens.push(Token::Text(word));
+            }
+            c => return Err(format!("unexpected character {c:?}")),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Everything up to closing character, if there's one at all
+fn until(chars: &mut impl Iterator<Item = char>, end: char) -> Option<String> {
+    let mut text = String::new();
+
+    for c in chars {
+        if c == end {
+            return Some(text);
+        }
+        text.push(c);
+    }
+
+    None
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn or(&mut self) -> std::result::Result<Condition, String> {
+        let mut left = self.and()?;
+
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            left = Condition::Or(Box::new(left), Box::new(self.and()?));
+        }
+
+        Ok(left)
+    }
+
+    fn and(&mut self) -> std::result::Result<Condition, String> {
+        let mut left = self.not()?;
+
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            left = Condition::And(Box::new(left), Box::new(self.not()?));
+        }
+
+        Ok(left)
+    }
+
+    fn not(&mut self) -> std::result::Result<Condition, String> {
+        if self.peek() == Some(&Token::Not) {
+            self.next();
+            return Ok(Condition::Not(Box::new(self.not()?)));
+        }
+
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> std::result::Result<Condition, String> {
+        let left = self.atom()?;
+
+        match self.peek() {
+            Some(Token::Equal) => {
+                self.next();
+                Ok(Condition::Equal(Box::new(left), Box::new(self.atom()?)))
+            }
+            Some(Token::NotEqual) => {
+                self.next();
+                Ok(Condition::NotEqual(Box::new(left), Box::new(self.atom()?)))
+            }
+            Some(Token::Text(t)) if t == "in" => {
+                self.next();
+                Ok(Condition::In(Box::new(left), Box::new(self.atom()?)))
+            }
+            _ => Ok(left),
+        }
+    }
+
+    fn atom(&mut self) -> std::result::Result<Condition, String> {
+        match self.next() {
+            Some(Token::Text(t)) => Ok(Condition::Literal(t)),
+            Some(Token::Variable(v)) => Ok(Condition::Variable(v)),
+            Some(Token::Open) => {
+                let inner = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(inner),
+                    _ => Err("missing closing parenthesis".into()),
+                }
+            }
+            Some(t) => Err(format!("unexpected {t:?}")),
+            None => Err("unexpected end of expression".into()),
+        }
+    }
+}
+
+impl Condition {
+    pub fn parse<T: AsRef<str>>(input: T) -> Result<Self> {
+        let invalid = |reason: String| Error::InvalidCondition(input.as_ref().to_owned(), reason);
+
+        let mut parser = Parser {
+            tokens: tokenize(input.as_ref()).map_err(invalid)?,
+            position: 0,
+        };
+
+        let condition = parser.or().map_err(invalid)?;
+
+        match parser.peek() {
+            None => Ok(condition),
+            Some(t) => Err(invalid(format!("unexpected {t:?}"))),
+        }
+    }
+
+    /// Value of operand, variables are looked up in globals
+    fn value(&self, global: &Globals) -> Result<String> {
+        match self {
+            Self::Literal(l) => Ok(l.to_owned()),
+            // Filters like @name|lowercase@ work here as well
+            Self::Variable(v) => global.interpolate(format!("@{v}@")),
+            other => other.evaluate(global).map(|b| b.to_string()),
+        }
+    }
+
+    /// Items of operand, choices of multiselect are taken as they are
+    fn items(&self, global: &Globals) -> Result<Vec<String>> {
+        match self {
+            Self::Variable(v) if global.contains_key(v) => Ok(global[v].items()),
+            other => Ok(Value::from(other.value(global)?).items()),
+        }
+    }
+
+    pub fn evaluate(&self, global: &Globals) -> Result<bool> {
+        Ok(match self {
+            Self::Literal(_) | Self::Variable(_) => truthy(&self.value(global)?),
+            Self::Equal(l, r) => l.value(global)? == r.value(global)?,
+            Self::NotEqual(l, r) => l.value(global)? != r.value(global)?,
+            Self::In(l, r) => {
+                let item = l.value(global)?;
+                r.items(global)?.contains(&item)
+            }
+            Self::Not(c) => !c.evaluate(global)?,
+            Self::And(l, r) => l.evaluate(global)? && r.evaluate(global)?,
+            Self::Or(l, r) => l.evaluate(global)? || r.evaluate(global)?,
+        })
+    }
+}
+
+/// Whether task with optional `when` should run at all
+pub fn applies(when: Option<&String>, global: &Globals) -> Result<bool> {
+    match when {
+        Some(w) => Condition::parse(w)?.evaluate(global),
+        None => Ok(true),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holds(when: &str, global: &Globals) -> bool {
+        Condition::parse(when).unwrap().evaluate(global).unwrap()
+    }
+
+    #[test]
+    fn binds_not_tighter_than_and_than_or() {
+        let variable = |n: &str| Box::new(Condition::Variable(n.into()));
+
+        assert_eq!(
+            Condition::parse("!@a@ && @b@ || @c@").unwrap(),
+            Condition::Or(
+                Box::new(Condition::And(
+                    Box::new(Condition::Not(variable("a"))),
+                    variable("b"),
+                )),
+                variable("c"),
+            )
+        );
+
+        let global = Globals::from([
+            ("a".to_owned(), "true".into()),
+            ("b".to_owned(), "true".into()),
+            ("c".to_owned(), "false".into()),
+        ]);
+
+        assert!(!holds("!@a@ && @b@ || @c@", &global));
+        assert!(holds("!@a@ && @b@ || !@c@", &global));
+        assert!(holds("!(@a@ && @c@)", &global));
+    }
+
+    #[test]
+    fn looks_up_items_of_lists() {
+        let global = Globals::from([(
+            "features".to_owned(),
+            Value::List(vec!["a,b".into(), "tokio".into()]),
+        )]);
+
+        assert!(holds("tokio in @features@", &global));
+        assert!(holds("'a,b' in @features@", &global));
+        assert!(!holds("a in @features@", &global));
+        assert!(holds("b in 'a,b'", &global));
+    }
+
+    #[test]
+    fn compares_with_quoted_literals() {
+        let global = Globals::from([("license".to_owned(), "Apache 2.0".into())]);
+
+        assert!(holds("@license@ == 'Apache 2.0'", &global));
+        assert!(holds("@license@ != \"MIT\"", &global));
+        assert!(!holds("'Apache 2.0' != @license@", &global));
+    }
+
+    #[test]
+    fn refuses_unterminated_quotes_and_references() {
+        for when in ["@a@ == 'x", "@a@ == \"x", "@a == x", "@@", "@a@ ==", "(@a@"] {
+            assert!(
+                matches!(Condition::parse(when), Err(Error::InvalidCondition(..))),
+                "{when}"
+            );
+        }
+    }
+}
@@ -1,17 +1,46 @@
 pub mod apply;
+pub mod condition;
 pub mod prelude;
 
 use crate::execute::{
     task::{Task, ToTask},
     Executor,
 };
+use crate::{Error, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use prelude::append::Append;
 use prelude::change::Change;
+use prelude::copy::Copy;
+use prelude::create::Create;
+use prelude::delete::Delete;
 use prelude::project::Project;
 use prelude::r#move::Move;
+use prelude::rename::Rename;
+use prelude::render::Render;
+use prelude::substitute::Substitute;
 use prelude::variable::Variable;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+/// Compile globs relative to template directory, where `*`
+/// stays within a single directory and `**` crosses them
+pub fn glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+
+    for pattern in patterns {
+        builder.add(
+            GlobBuilder::new(pattern.trim_start_matches("./"))
+                .literal_separator(true)
+                .build()
+                .map_err(|e| Error::InvalidGlob(e.to_string()))?,
+        );
+    }
+
+    builder
+        .build()
+        .map_err(|e| Error::InvalidGlob(e.to_string()))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Template {
     project: Project,
@@ -25,6 +54,34 @@ pub struct Template {
     #[serde(default)]
     replace: Vec<Move>,
 
+    #[serde(default)]
+    delete: Vec<Delete>,
+
+    #[serde(default)]
+    create: Vec<Create>,
+
+    #[serde(default)]
+    append: Vec<Append>,
+
+    #[serde(default)]
+    prepend: Vec<Append>,
+
+    #[serde(default)]
+    copy: Vec<Copy>,
+
+    /// Globs of files to render with template engine,
+    /// files ending with `.bleur` are rendered anyway
+    #[serde(default)]
+    render: Vec<String>,
+
+    /// Replace @variable@ references in every text file
+    #[serde(default)]
+    substitute: bool,
+
+    /// Rename files and directories with @variable@ in their names
+    #[serde(default)]
+    rename: bool,
+
     /// Only for runtime use!
     /// For path awareness at recursive copying.
     #[serde(skip)]
@@ -38,6 +95,14 @@ impl Template {
             variable: self.variable,
             change: self.change,
             replace: self.replace,
+            delete: self.delete,
+            create: self.create,
+            append: self.append,
+            prepend: self.prepend,
+            copy: self.copy,
+            render: self.render,
+            substitute: self.substitute,
+            rename: self.rename,
             path,
         }
     }
@@ -77,6 +142,59 @@ impl Template {
                 .collect::<Vec<Task>>(),
         );
 
+        // Appending deletions
+        tasks.extend(
+            self.delete
+                .iter()
+                .map(|v| v.to_owned().to_task(&self.path))
+                .collect::<Vec<Task>>(),
+        );
+
+        // Appending new files
+        tasks.extend(
+            self.create
+                .iter()
+                .map(|v| v.to_owned().to_task(&self.path))
+                .collect::<Vec<Task>>(),
+        );
+
+        // Appending lines to the end of files
+        tasks.extend(
+            self.append
+                .iter()
+                .map(|v| v.to_owned().to_task(&self.path))
+                .collect::<Vec<Task>>(),
+        );
+
+        // Appending lines to the beginning of files
+        tasks.extend(
+            self.prepend
+                .iter()
+                .map(|v| v.to_owned().prepending().to_task(&self.path))
+                .collect::<Vec<Task>>(),
+        );
+
+        // Appending copies
+        tasks.extend(
+            self.copy
+                .iter()
+                .map(|v| v.to_owned().to_task(&self.path))
+                .collect::<Vec<Task>>(),
+        );
+
+        // Appending substitution across whole tree
+        if self.substitute {
+            tasks.push(Substitute::default().to_task(&self.path));
+        }
+
+        // Appending rendering of files
+        tasks.push(Render::new(self.render.clone()).to_task(&self.path));
+
+        // Appending renaming of whatever has variables in its name
+        if self.rename {
+            tasks.push(Rename::default().to_task(&self.path));
+        }
+
         // Append other types here...
 
         // Sort tasks
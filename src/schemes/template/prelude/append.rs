@@ -0,0 +1,173 @@
+use crate::{
+    execute::task::{Task, ToTask},
+    manager::Glubtastic,
+    schemes::template::{
+        apply::Apply,
+        prelude::{change::diff, r#move::inside},
+    },
+    value::Globals,
+    Result,
+};
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, PartialOrd, Eq, Ord)]
+pub struct Append {
+    /// File to add lines to, created if it doesn't exist
+    path: String,
+
+    /// Computable lines which might contain global variables
+    value: String,
+
+    /// Functions to apply on lines
+    #[serde(default)]
+    apply: String,
+
+    /// Run only if this expression holds
+    #[serde(default)]
+    when: Option<String>,
+
+    /// Only for runtime use, whether lines go to the beginning
+    #[serde(skip)]
+    prepend: bool,
+
+    /// Only for runtime use, template directory
+    #[serde(skip)]
+    root: PathBuf,
+}
+
+impl Append {
+    /// Put lines at the beginning of file instead
+    pub fn prepending(self) -> Self {
+        Self {
+            prepend: true,
+            ..self
+        }
+    }
+
+    pub fn when(&self) -> Option<&String> {
+        self.when.as_ref()
+    }
+
+    pub fn apply(&self) -> &str {
+        &self.apply
+    }
+
+    pub fn label(&self) -> &str {
+        match self.prepend {
+            true => "prepend",
+            false => "append",
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Computed path, contents before and after adding lines
+    fn render(&self, global: &Globals) -> Result<(PathBuf, String, String)> {
+        let path = inside(&self.root, Path::new(&global.interpolate(&self.path)?))?;
+        let mut lines = Apply::parse(&self.apply)?.execute(global.interpolate(&self.value)?);
+        let original = match path.exists() {
+            true => fs::read_to_string(&path)?,
+            false => String::new(),
+        };
+
+        // Lines stay lines on both sides of what's added
+        if !lines.ends_with('\n') {
+            lines.push('\n');
+        }
+
+        let contents = match self.prepend {
+            true => lines + &original,
+            false if original.is_empty() || original.ends_with('\n') => original.clone() + &lines,
+            false => format!("{original}\n{lines}"),
+        };
+
+        Ok((path, original, contents))
+    }
+
+    pub fn execute(&self, global: &mut Globals) -> Result<()> {
+        let (path, _, contents) = self.render(global)?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, contents)?;
+
+        Ok(())
+    }
+
+    /// Unified diff of what execution would do
+    pub fn plan(&self, global: &Globals, root: &Path) -> Result<String> {
+        let (path, original, contents) = self.render(global)?;
+
+        Ok(diff(
+            self.label().yellow(),
+            path.strip_prefix(root).unwrap_or(&path),
+            &original,
+            &contents,
+        ))
+    }
+}
+
+impl ToTask for Append {
+    fn to_task(self, path: &Path) -> Task {
+        Task::Append(Append {
+            path: self.path,
+            value: self.value,
+            apply: self.apply,
+            when: self.when,
+            prepend: self.prepend,
+            root: path.to_path_buf(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn append(root: &Path, path: &str, value: &str) -> Append {
+        Append {
+            path: path.into(),
+            value: value.into(),
+            apply: String::new(),
+            when: None,
+            prepend: false,
+            root: root.to_path_buf(),
+        }
+    }
+
+    #[test]
+    fn adds_lines_to_both_ends() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("README.md"), "body").unwrap();
+        let mut global = Globals::from([("name".to_owned(), "app".into())]);
+
+        append(root.path(), "README.md", "end of @name@")
+            .execute(&mut global)
+            .unwrap();
+        append(root.path(), "README.md", "# @name@")
+            .prepending()
+            .execute(&mut global)
+            .unwrap();
+        append(root.path(), "new/file.txt", "fresh")
+            .execute(&mut global)
+            .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(root.path().join("README.md")).unwrap(),
+            "# app\nbody\nend of app\n"
+        );
+        assert_eq!(
+            fs::read_to_string(root.path().join("new/file.txt")).unwrap(),
+            "fresh\n"
+        );
+    }
+}
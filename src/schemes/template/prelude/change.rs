@@ -1,24 +1,65 @@
 use crate::{
     execute::task::{Task, ToTask},
     manager::Glubtastic,
-    schemes::template::apply::Apply,
+    schemes::template::{apply::Apply, glob_set},
+    value::Globals,
     Error, Result,
 };
+use owo_colors::OwoColorize;
+use regex::{Captures, Regex};
 use serde::{Deserialize, Serialize};
+use similar::TextDiff;
 use std::{
-    collections::HashMap,
+    fmt::Display,
     fs::{self, OpenOptions},
     io::Write,
     path::{Path, PathBuf},
 };
+use walkdir::WalkDir;
+
+/// Either a single path or glob, or a list of them
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, PartialOrd, Eq, Ord)]
+#[serde(untagged)]
+pub enum Sources {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Sources {
+    fn patterns(&self) -> Vec<String> {
+        match self {
+            Self::One(p) => vec![p.to_owned()],
+            Self::Many(p) => p.to_owned(),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, PartialOrd, Eq, Ord)]
 pub struct Change {
     /// Catch phrase or word to locate
     placeholder: String,
 
-    /// Where the file is located
-    source: PathBuf,
+    /// Treat placeholder as regex, value may refer to captures as $1
+    #[serde(default)]
+    regex: bool,
+
+    /// Replace only first N occurrences in each file
+    count: Option<usize>,
+
+    /// Where the files are located, globs like `src/**/*.rs` work too
+    source: Sources,
+
+    /// Globs of files to leave alone even if source matches them
+    #[serde(default)]
+    exclude: Vec<String>,
+
+    /// Don't complain if source matches no files at all
+    #[serde(default)]
+    optional: bool,
+
+    /// Only for runtime use, template directory
+    #[serde(skip)]
+    root: PathBuf,
 
     /// Computable value which might contain global variables
     value: String,
@@ -26,49 +67,241 @@ pub struct Change {
     /// Functions to apply on value
     #[serde(default)]
     apply: String,
+
+    /// Run only if this expression holds
+    #[serde(default)]
+    when: Option<String>,
 }
 
 impl Change {
-    pub fn execute(&self, global: &mut HashMap<String, String>) -> Result<()> {
-        let variables: Vec<(String, Option<&String>)> = global
-            .globs(self.value.clone())
+    pub fn when(&self) -> Option<&String> {
+        self.when.as_ref()
+    }
+
+    pub fn apply(&self) -> &str {
+        &self.apply
+    }
+
+    /// Sources as they were written in bleur.toml
+    pub fn source(&self) -> String {
+        self.source.patterns().join(", ")
+    }
+
+    /// Sources naming an existing file are matched literally, so paths
+    /// like `pages/[id].tsx` aren't read as globs, the rest are globs
+    fn sources(&self) -> (Vec<PathBuf>, Vec<String>) {
+        let (literal, globs): (Vec<_>, Vec<_>) = self
+            .source
+            .patterns()
+            .into_iter()
+            .partition(|p| self.root.join(p.trim_start_matches("./")).is_file());
+
+        let literal = literal
             .iter()
-            .map(|m| (m.to_owned(), global.get(m)))
+            .map(|p| PathBuf::from(p.trim_start_matches("./")))
             .collect();
 
-        let mut change = self.value.clone();
+        (literal, globs)
+    }
+
+    /// Make sure globs and regex compile
+    pub fn check(&self) -> Result<()> {
+        glob_set(&self.sources().1)?;
+        glob_set(&self.exclude)?;
 
-        for var in variables {
-            if let Some(v) = var.1 {
-                change = change.replace(&format!("@{}@", var.0), v);
-                continue;
+        if self.regex {
+            Regex::new(&self.placeholder)?;
+        }
+
+        Ok(())
+    }
+
+    /// Text files matching source but not exclude
+    fn files(&self) -> Result<Vec<PathBuf>> {
+        let (literal, globs) = self.sources();
+        let source = glob_set(&globs)?;
+        let exclude = glob_set(&self.exclude)?;
+        let mut files = Vec::new();
+
+        for entry in WalkDir::new(&self.root).min_depth(1).sort_by_file_name() {
+            let entry = entry.map_err(|e| Error::IOError(e.into()))?;
+            let relative = entry
+                .path()
+                .strip_prefix(&self.root)
+                .unwrap_or(entry.path());
+
+            if entry.file_type().is_file()
+                && (literal.iter().any(|l| l == relative) || source.is_match(relative))
+                && !exclude.is_match(relative)
+            {
+                files.push(entry.into_path());
             }
+        }
+
+        if files.is_empty() && !self.optional {
+            return Err(Error::NoMatchingFiles(self.source()));
+        }
+
+        Ok(files)
+    }
+
+    /// Contents of each text file before and after the change
+    fn render(&self, global: &Globals) -> Result<Vec<(PathBuf, String, String)>> {
+        let applications = Apply::parse(&self.apply)?;
+        let regex = self
+            .regex
+            .then(|| Regex::new(&self.placeholder))
+            .transpose()?;
+
+        // Dollars of variable values aren't references to captures
+        let change = match regex {
+            Some(_) => global
+                .iter()
+                .map(|(k, v)| (k.to_owned(), v.to_owned().map(|v| v.replace('$', "$$"))))
+                .collect::<Globals>()
+                .interpolate(&self.value)?,
+            None => global.interpolate(&self.value)?,
+        };
+        // Zero means all of them for both kinds of replacement
+        let count = self.count.unwrap_or(0);
+        let mut rendered = Vec::new();
 
-            return Err(Error::NoSuchVariable(var.0.clone()));
+        for file in self.files()? {
+            // Binary files can't have placeholders in them
+            let Some(original) = text(&file)? else {
+                continue;
+            };
+
+            let contents = match &regex {
+                // Functions apply to value after captures got expanded
+                Some(regex) => regex
+                    .replacen(&original, count, |caps: &Captures| {
+                        let mut value = String::new();
+                        caps.expand(&change, &mut value);
+                        applications.execute(value)
+                    })
+                    .into_owned(),
+                None if count == 0 => {
+                    original.replace(&self.placeholder, &applications.execute(&change))
+                }
+                None => original.replacen(&self.placeholder, &applications.execute(&change), count),
+            };
+
+            rendered.push((file, original, contents));
         }
 
-        let applications = Apply::parse(self.apply.clone());
-        let contents = fs::read_to_string(self.source.clone())?
-            .replace(&self.placeholder, &applications.execute(change));
+        Ok(rendered)
+    }
+
+    pub fn execute(&self, global: &mut Globals) -> Result<()> {
+        for (file, original, contents) in self.render(global)? {
+            if original == contents {
+                continue;
+            }
 
-        let mut file = OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .open(&self.source)?;
+            let mut file = OpenOptions::new().write(true).truncate(true).open(&file)?;
 
-        file.write_all(contents.as_bytes())?;
+            file.write_all(contents.as_bytes())?;
+        }
 
         Ok(())
     }
+
+    /// Unified diff of what execution would do
+    pub fn plan(&self, global: &Globals, root: &Path) -> Result<String> {
+        Ok(self
+            .render(global)?
+            .iter()
+            .filter(|(_, original, contents)| original != contents)
+            .map(|(file, original, contents)| {
+                diff(
+                    "change".yellow(),
+                    file.strip_prefix(root).unwrap_or(file),
+                    original,
+                    contents,
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+}
+
+/// Unified diff of file contents under label and its name
+pub fn diff(label: impl Display, name: &Path, original: &str, contents: &str) -> String {
+    format!(
+        "{} {}\n{}",
+        label,
+        name.display(),
+        TextDiff::from_lines(original, contents)
+            .unified_diff()
+            .header(
+                &format!("a/{}", name.display()),
+                &format!("b/{}", name.display())
+            )
+    )
+}
+
+/// Contents of file, unless it looks binary
+pub fn text(path: &Path) -> Result<Option<String>> {
+    let bytes = fs::read(path)?;
+
+    if bytes.iter().take(8000).any(|b| *b == 0) {
+        return Ok(None);
+    }
+
+    Ok(String::from_utf8(bytes).ok())
 }
 
 impl ToTask for Change {
     fn to_task(self, path: &Path) -> Task {
         Task::Change(Change {
             placeholder: self.placeholder,
-            source: path.join(self.source),
+            regex: self.regex,
+            count: self.count,
+            source: self.source,
+            exclude: self.exclude,
+            optional: self.optional,
+            root: path.to_path_buf(),
             value: self.value,
             apply: self.apply,
+            when: self.when,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_existing_paths_literally() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("pages")).unwrap();
+        fs::create_dir_all(root.path().join("{{cookiecutter}}")).unwrap();
+        fs::write(root.path().join("pages/[id].tsx"), "x").unwrap();
+        fs::write(root.path().join("pages/i.tsx"), "x").unwrap();
+        fs::write(root.path().join("{{cookiecutter}}/x"), "x").unwrap();
+
+        let change: Change = toml::from_str(
+            r#"
+            source = ["pages/[id].tsx", "{{cookiecutter}}/x"]
+            placeholder = "x"
+            value = "y"
+            "#,
+        )
+        .unwrap();
+        let change = Change {
+            root: root.path().to_path_buf(),
+            ..change
+        };
+
+        change.check().unwrap();
+        assert_eq!(
+            change.files().unwrap(),
+            vec![
+                root.path().join("pages/[id].tsx"),
+                root.path().join("{{cookiecutter}}/x"),
+            ]
+        );
+    }
+}
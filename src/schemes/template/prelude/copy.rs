@@ -0,0 +1,171 @@
+use crate::{
+    execute::task::{Task, ToTask},
+    manager::Glubtastic,
+    method::local::copy_tree,
+    schemes::template::{apply::Apply, prelude::r#move::inside},
+    value::Globals,
+    Error, Result,
+};
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, PartialOrd, Eq, Ord)]
+pub struct Copy {
+    /// Take a file or directory at ...
+    from: String,
+
+    /// And then put a copy of it to ...
+    to: String,
+
+    /// Functions to apply on destination
+    #[serde(default)]
+    apply: String,
+
+    /// Run only if this expression holds
+    #[serde(default)]
+    when: Option<String>,
+
+    /// Only for runtime use, template directory
+    #[serde(skip)]
+    root: PathBuf,
+}
+
+impl Copy {
+    pub fn when(&self) -> Option<&String> {
+        self.when.as_ref()
+    }
+
+    pub fn apply(&self) -> &str {
+        &self.apply
+    }
+
+    pub fn from(&self) -> &str {
+        &self.from
+    }
+
+    /// Computed source and destination of copy
+    fn paths(&self, global: &Globals) -> Result<(PathBuf, PathBuf)> {
+        let from = inside(&self.root, Path::new(&global.interpolate(&self.from)?))?;
+        let to = Apply::parse(&self.apply)?.execute(global.interpolate(&self.to)?);
+        let to = inside(&self.root, Path::new(&to))?;
+
+        if !from.exists() {
+            return Err(Error::InvalidFilePath(from));
+        }
+
+        // Walking a directory while writing into it never ends
+        if to.starts_with(&from) {
+            return Err(Error::CopyIntoItself(
+                to.strip_prefix(&self.root).unwrap_or(&to).to_path_buf(),
+            ));
+        }
+
+        // Copying is not meant for overwriting what template has
+        if to.exists() {
+            return Err(Error::FileAlreadyExists(
+                to.strip_prefix(&self.root).unwrap_or(&to).to_path_buf(),
+            ));
+        }
+
+        Ok((from, to))
+    }
+
+    pub fn execute(&self, global: &mut Globals) -> Result<()> {
+        let (from, to) = self.paths(global)?;
+
+        if from.is_dir() {
+            fs::create_dir_all(&to)?;
+            return copy_tree(&from, &to);
+        }
+
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::copy(from, to)?;
+
+        Ok(())
+    }
+
+    /// Source and destination of what execution would do
+    pub fn plan(&self, global: &Globals, root: &Path) -> Result<String> {
+        let (from, to) = self.paths(global)?;
+
+        Ok(format!(
+            "{} {} -> {}",
+            "copy".blue(),
+            from.strip_prefix(root).unwrap_or(&from).display(),
+            to.strip_prefix(root).unwrap_or(&to).display()
+        ))
+    }
+}
+
+impl ToTask for Copy {
+    fn to_task(self, path: &Path) -> Task {
+        Task::Copy(Copy {
+            from: self.from,
+            to: self.to,
+            apply: self.apply,
+            when: self.when,
+            root: path.to_path_buf(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn copy(root: &Path, from: &str, to: &str) -> Copy {
+        Copy {
+            from: from.into(),
+            to: to.into(),
+            apply: String::new(),
+            when: None,
+            root: root.to_path_buf(),
+        }
+    }
+
+    #[test]
+    fn copies_files_and_directories() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("shared/inner")).unwrap();
+        fs::write(root.path().join("shared/inner/a.txt"), "a").unwrap();
+        let mut global = Globals::from([("name".to_owned(), "app".into())]);
+
+        copy(root.path(), "shared", "@name@/shared")
+            .execute(&mut global)
+            .unwrap();
+        copy(root.path(), "shared/inner/a.txt", "b.txt")
+            .execute(&mut global)
+            .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(root.path().join("app/shared/inner/a.txt")).unwrap(),
+            "a"
+        );
+        assert_eq!(fs::read_to_string(root.path().join("b.txt")).unwrap(), "a");
+
+        // Existing files are never overwritten
+        assert!(copy(root.path(), "b.txt", "shared/inner/a.txt")
+            .execute(&mut global)
+            .is_err());
+    }
+
+    #[test]
+    fn refuses_copying_into_itself() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("src")).unwrap();
+        let mut global = Globals::new();
+
+        for to in ["src/backup", "./src/backup", "", "."] {
+            assert!(copy(root.path(), "src", to).execute(&mut global).is_err());
+        }
+
+        assert!(!root.path().join("src/backup").exists());
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{
+    execute::task::{Task, ToTask},
+    manager::Glubtastic,
+    schemes::template::{
+        apply::Apply,
+        prelude::{change::diff, r#move::inside},
+    },
+    value::Globals,
+    Error, Result,
+};
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, PartialOrd, Eq, Ord)]
+pub struct Create {
+    /// Where new file goes, might contain global variables
+    path: String,
+
+    /// Computable contents which might contain global variables
+    value: String,
+
+    /// Functions to apply on contents
+    #[serde(default)]
+    apply: String,
+
+    /// Run only if this expression holds
+    #[serde(default)]
+    when: Option<String>,
+
+    /// Only for runtime use, template directory
+    #[serde(skip)]
+    root: PathBuf,
+}
+
+impl Create {
+    pub fn when(&self) -> Option<&String> {
+        self.when.as_ref()
+    }
+
+    pub fn apply(&self) -> &str {
+        &self.apply
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Computed path and contents of new file
+    fn render(&self, global: &Globals) -> Result<(PathBuf, String)> {
+        let path = inside(&self.root, Path::new(&global.interpolate(&self.path)?))?;
+        let contents = Apply::parse(&self.apply)?.execute(global.interpolate(&self.value)?);
+
+        // Creating is not meant for overwriting what template has
+        if path.exists() {
+            return Err(Error::FileAlreadyExists(
+                path.strip_prefix(&self.root).unwrap_or(&path).to_path_buf(),
+            ));
+        }
+
+        Ok((path, contents))
+    }
+
+    pub fn execute(&self, global: &mut Globals) -> Result<()> {
+        let (path, contents) = self.render(global)?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, contents)?;
+
+        Ok(())
+    }
+
+    /// Contents of file which execution would create
+    pub fn plan(&self, global: &Globals, root: &Path) -> Result<String> {
+        let (path, contents) = self.render(global)?;
+
+        Ok(diff(
+            "create".green(),
+            path.strip_prefix(root).unwrap_or(&path),
+            "",
+            &contents,
+        ))
+    }
+}
+
+impl ToTask for Create {
+    fn to_task(self, path: &Path) -> Task {
+        Task::Create(Create {
+            path: self.path,
+            value: self.value,
+            apply: self.apply,
+            when: self.when,
+            root: path.to_path_buf(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create(root: &Path, path: &str, value: &str) -> Create {
+        Create {
+            path: path.into(),
+            value: value.into(),
+            apply: "uppercase".into(),
+            when: None,
+            root: root.to_path_buf(),
+        }
+    }
+
+    #[test]
+    fn writes_new_files_only() {
+        let root = tempfile::tempdir().unwrap();
+        let mut global = Globals::from([("name".to_owned(), "app".into())]);
+
+        create(root.path(), "src/@name@.txt", "hi @name@")
+            .execute(&mut global)
+            .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(root.path().join("src/app.txt")).unwrap(),
+            "HI APP"
+        );
+        assert!(create(root.path(), "src/app.txt", "again")
+            .execute(&mut global)
+            .is_err());
+        assert!(create(root.path(), "", "x").execute(&mut global).is_err());
+    }
+}
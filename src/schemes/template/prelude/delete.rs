@@ -0,0 +1,130 @@
+use crate::{
+    execute::task::{Task, ToTask},
+    manager::Glubtastic,
+    schemes::template::prelude::r#move::inside,
+    value::Globals,
+    Error, Result,
+};
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, PartialOrd, Eq, Ord)]
+pub struct Delete {
+    /// File or directory to remove, might contain global variables
+    path: String,
+
+    /// Don't complain if there's nothing to remove
+    #[serde(default)]
+    optional: bool,
+
+    /// Run only if this expression holds
+    #[serde(default)]
+    when: Option<String>,
+
+    /// Only for runtime use, template directory
+    #[serde(skip)]
+    root: PathBuf,
+}
+
+impl Delete {
+    pub fn when(&self) -> Option<&String> {
+        self.when.as_ref()
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Computed path of what should be removed, if it exists
+    fn target(&self, global: &Globals) -> Result<Option<PathBuf>> {
+        let path = inside(&self.root, Path::new(&global.interpolate(&self.path)?))?;
+
+        match path.symlink_metadata().is_ok() {
+            true => Ok(Some(path)),
+            false if self.optional => Ok(None),
+            false => Err(Error::InvalidFilePath(path)),
+        }
+    }
+
+    pub fn execute(&self, global: &mut Globals) -> Result<()> {
+        match self.target(global)? {
+            Some(path) if path.is_dir() => fs::remove_dir_all(path)?,
+            Some(path) => fs::remove_file(path)?,
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    /// Path which execution would remove
+    pub fn plan(&self, global: &Globals, root: &Path) -> Result<String> {
+        Ok(self
+            .target(global)?
+            .map(|p| {
+                format!(
+                    "{} {}",
+                    "delete".red(),
+                    p.strip_prefix(root).unwrap_or(&p).display()
+                )
+            })
+            .unwrap_or_default())
+    }
+}
+
+impl ToTask for Delete {
+    fn to_task(self, path: &Path) -> Task {
+        Task::Delete(Delete {
+            path: self.path,
+            optional: self.optional,
+            when: self.when,
+            root: path.to_path_buf(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delete(root: &Path, path: &str) -> Delete {
+        Delete {
+            path: path.into(),
+            optional: false,
+            when: None,
+            root: root.to_path_buf(),
+        }
+    }
+
+    #[test]
+    fn removes_files_and_directories() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("docs/inner")).unwrap();
+        fs::write(root.path().join("app.lock"), "").unwrap();
+        let mut global = Globals::from([("name".to_owned(), "app".into())]);
+
+        delete(root.path(), "@name@.lock")
+            .execute(&mut global)
+            .unwrap();
+        delete(root.path(), "docs").execute(&mut global).unwrap();
+
+        assert!(!root.path().join("app.lock").exists());
+        assert!(!root.path().join("docs").exists());
+        assert!(delete(root.path(), "missing").execute(&mut global).is_err());
+    }
+
+    #[test]
+    fn refuses_template_directory_itself() {
+        let root = tempfile::tempdir().unwrap();
+        let mut global = Globals::from([("empty".to_owned(), "".into())]);
+
+        for path in ["", ".", "./", "@empty@", "../x", "/tmp"] {
+            assert!(delete(root.path(), path).execute(&mut global).is_err());
+        }
+
+        assert!(root.path().exists());
+    }
+}
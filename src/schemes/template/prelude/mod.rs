@@ -1,4 +1,11 @@
+pub mod append;
 pub mod change;
+pub mod copy;
+pub mod create;
+pub mod delete;
 pub mod r#move;
 pub mod project;
+pub mod rename;
+pub mod render;
+pub mod substitute;
 pub mod variable;
@@ -1,13 +1,16 @@
 use crate::{
     execute::task::{Task, ToTask},
     manager::Glubtastic,
+    method::local::copy_tree,
     schemes::template::apply::Apply,
+    value::Globals,
     Error, Result,
 };
+use owo_colors::OwoColorize;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
-    path::{Path, PathBuf},
+    fs, io,
+    path::{Component, Path, PathBuf},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, PartialOrd, Eq, Ord)]
@@ -21,48 +24,120 @@ pub struct Move {
     /// Functions to apply on value
     #[serde(default)]
     apply: String,
+
+    /// Run only if this expression holds
+    #[serde(default)]
+    when: Option<String>,
+
+    /// Only for runtime use, template directory
+    #[serde(skip)]
+    root: PathBuf,
 }
 
 impl Move {
-    pub fn execute(&self, global: &mut HashMap<String, String>) -> Result<()> {
+    pub fn when(&self) -> Option<&String> {
+        self.when.as_ref()
+    }
+
+    pub fn apply(&self) -> &str {
+        &self.apply
+    }
+
+    pub fn from(&self) -> &Path {
+        &self.from
+    }
+
+    /// Computed path of where file should be moved, functions
+    /// apply only to what's written in bleur.toml, not template path
+    fn destination(&self, global: &Globals) -> Result<PathBuf> {
         let to = self
             .to
             .to_str()
-            .ok_or(Error::InvalidFilePath(self.to.clone()))?
-            .to_owned();
+            .ok_or(Error::InvalidFilePath(self.to.clone()))?;
 
-        let variables: Vec<(String, Option<&String>)> = global
-            .globs(to.clone())
-            .iter()
-            .map(|m| (m.to_owned(), global.get(m)))
-            .collect();
+        let file_name = global.interpolate(to)?;
+        let applications = Apply::parse(&self.apply)?;
 
-        let mut file_name = to;
+        inside(&self.root, Path::new(&applications.execute(file_name)))
+    }
 
-        for var in variables {
-            if let Some(v) = var.1 {
-                file_name = file_name.replace(&format!("@{}@", var.0), v);
-                continue;
-            }
+    pub fn execute(&self, global: &mut Globals) -> Result<()> {
+        relocate(
+            &inside(&self.root, &self.from)?,
+            &self.destination(global)?,
+            true,
+        )
+    }
 
-            return Err(Error::NoSuchVariable(var.0.clone()));
-        }
+    /// Old and new path of what execution would do
+    pub fn plan(&self, global: &Globals, root: &Path) -> Result<String> {
+        let from = self.root.join(&self.from);
+        let to = self.destination(global)?;
 
-        let applications = Apply::parse(self.apply.clone());
-        file_name = applications.execute(file_name);
+        Ok(format!(
+            "{} {} -> {}",
+            "move".blue(),
+            from.strip_prefix(root).unwrap_or(&from).display(),
+            to.strip_prefix(root).unwrap_or(&to).display()
+        ))
+    }
+}
+
+/// Path relative to template directory, which can't escape it
+/// nor be the template directory itself, e.g. empty or `.`
+pub fn inside(root: &Path, path: &Path) -> Result<PathBuf> {
+    let mut components = path.components();
+
+    match components
+        .clone()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        && components.any(|c| matches!(c, Component::Normal(_)))
+    {
+        true => Ok(root.join(path)),
+        false => Err(Error::InvalidFilePath(path.to_path_buf())),
+    }
+}
 
-        std::fs::rename(&self.from, file_name).map_err(|e| Error::CantMoveFile(e.to_string()))?;
+/// Move file or directory, creating missing parents and copying
+/// when it has to go to another filesystem. Existing file at
+/// destination is replaced only if overwrite is allowed
+pub fn relocate(from: &Path, to: &Path, overwrite: bool) -> Result<()> {
+    let failed =
+        |e: io::Error| Error::CantMoveFile(format!("{} -> {}: {e}", from.display(), to.display()));
 
-        Ok(())
+    match fs::symlink_metadata(to) {
+        Ok(meta) if overwrite && !meta.is_dir() => fs::remove_file(to).map_err(failed)?,
+        Ok(_) => return Err(failed(io::ErrorKind::AlreadyExists.into())),
+        Err(_) => {}
+    }
+
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).map_err(failed)?;
+    }
+
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            if from.is_dir() {
+                fs::create_dir_all(to).map_err(failed)?;
+                copy_tree(from, to)?;
+                fs::remove_dir_all(from).map_err(failed)
+            } else {
+                fs::copy(from, to).map_err(failed)?;
+                fs::remove_file(from).map_err(failed)
+            }
+        }
+        result => result.map_err(failed),
     }
 }
 
 impl ToTask for Move {
     fn to_task(self, path: &Path) -> Task {
         Task::Move(Move {
-            from: path.join(self.from),
-            to: path.join(self.to),
+            from: self.from,
+            to: self.to,
             apply: self.apply,
+            when: self.when,
+            root: path.to_path_buf(),
         })
     }
 }
@@ -0,0 +1,122 @@
+use crate::{
+    execute::task::{Task, ToTask},
+    manager::{Glubtastic, REGEX},
+    schemes::template::prelude::r#move::relocate,
+    value::Globals,
+    Error, Result,
+};
+use owo_colors::OwoColorize;
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Renames every file and directory with @variable@ in its name
+#[derive(Debug, Clone, Default, PartialEq, PartialOrd, Eq, Ord)]
+pub struct Rename {
+    /// Template directory
+    root: PathBuf,
+}
+
+impl Rename {
+    /// Old and new paths, deepest first so that renaming a
+    /// directory doesn't invalidate paths of its contents
+    fn renames(&self, global: &Globals) -> Result<Vec<(PathBuf, PathBuf)>> {
+        let mut renames = Vec::new();
+
+        for entry in WalkDir::new(&self.root)
+            .min_depth(1)
+            .contents_first(true)
+            .sort_by_file_name()
+        {
+            let entry = entry.map_err(|e| Error::IOError(e.into()))?;
+            let name = entry.file_name().to_string_lossy();
+
+            if !REGEX.is_match(&name) {
+                continue;
+            }
+
+            let renamed = global.substitute(&name);
+
+            // Values like ../x or a/b would take file out of its directory
+            if !matches!(
+                Path::new(&renamed).components().collect::<Vec<_>>()[..],
+                [Component::Normal(_)]
+            ) || renamed.contains(['/', '\\'])
+            {
+                return Err(Error::InvalidFilePath(PathBuf::from(renamed)));
+            }
+
+            if renamed != name {
+                let to = entry.path().with_file_name(renamed);
+                renames.push((entry.into_path(), to));
+            }
+        }
+
+        Ok(renames)
+    }
+
+    pub fn execute(&self, global: &mut Globals) -> Result<()> {
+        self.renames(global)?
+            .iter()
+            .try_for_each(|(from, to)| relocate(from, to, false))
+    }
+
+    /// Old and new path of everything to be renamed
+    pub fn plan(&self, global: &Globals, root: &Path) -> Result<String> {
+        let relative = |p: &Path| p.strip_prefix(root).unwrap_or(p).display().to_string();
+
+        Ok(self
+            .renames(global)?
+            .iter()
+            .map(|(from, to)| format!("{} {} -> {}", "rename".blue(), relative(from), relative(to)))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+}
+
+impl ToTask for Rename {
+    fn to_task(self, path: &Path) -> Task {
+        Task::Rename(Rename {
+            root: path.to_path_buf(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn renames_within_same_directory() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("src/@name@")).unwrap();
+        fs::write(root.path().join("src/@name@/@name@.rs"), "").unwrap();
+        let mut global = Globals::from([("name".to_owned(), "app".into())]);
+
+        Rename {
+            root: root.path().to_path_buf(),
+        }
+        .execute(&mut global)
+        .unwrap();
+
+        assert!(root.path().join("src/app/app.rs").exists());
+    }
+
+    #[test]
+    fn refuses_names_leaving_directory() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("src/@name@")).unwrap();
+        fs::write(root.path().join("src/@name@/lib.rs"), "").unwrap();
+
+        for name in ["../../escape", "a/b", "..", ".", ""] {
+            let mut global = Globals::from([("name".to_owned(), name.into())]);
+            let rename = Rename {
+                root: root.path().to_path_buf(),
+            };
+
+            assert!(rename.execute(&mut global).is_err(), "{name}");
+        }
+
+        assert!(root.path().join("src/@name@/lib.rs").exists());
+    }
+}
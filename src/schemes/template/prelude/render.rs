@@ -0,0 +1,240 @@
+use crate::{
+    execute::task::{Task, ToTask},
+    schemes::template::{apply::Apply, glob_set},
+    value::{self, Globals},
+    Error, Result,
+};
+use globset::GlobSet;
+use minijinja::{
+    escape_formatter, path_loader, syntax::SyntaxConfig, value::ValueKind, AutoEscape, Environment,
+    UndefinedBehavior, Value,
+};
+use owo_colors::OwoColorize;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use walkdir::WalkDir;
+
+/// Files with this suffix are always rendered and lose it
+pub const SUFFIX: &str = "bleur";
+
+#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord)]
+pub struct Render {
+    /// Template directory, includes are looked up from here
+    root: PathBuf,
+
+    /// Globs of files to render in place, besides `.bleur` ones
+    patterns: Vec<String>,
+}
+
+impl Render {
+    pub fn new(patterns: Vec<String>) -> Self {
+        Self {
+            root: PathBuf::new(),
+            patterns,
+        }
+    }
+
+    pub fn patterns(&self) -> Result<GlobSet> {
+        glob_set(&self.patterns)
+    }
+
+    /// Files to render along with where rendered result goes
+    fn files(&self) -> Result<Vec<(PathBuf, PathBuf)>> {
+        let patterns = self.patterns()?;
+        let mut files = Vec::new();
+
+        for entry in WalkDir::new(&self.root).min_depth(1).sort_by_file_name() {
+            let entry = entry.map_err(|e| Error::IOError(e.into()))?;
+
+            if entry.file_type().is_dir() {
+                continue;
+            }
+
+            let path = entry.path();
+            let relative = path.strip_prefix(&self.root).unwrap_or(path);
+
+            if path.extension().is_some_and(|e| e == SUFFIX) {
+                files.push((path.to_path_buf(), path.with_extension("")));
+            } else if patterns.is_match(relative) {
+                files.push((path.to_path_buf(), path.to_path_buf()));
+            }
+        }
+
+        Ok(files)
+    }
+
+    fn environment(&self) -> Environment<'static> {
+        let mut environment = Environment::new();
+
+        // Missing variables are mistakes, not empty strings
+        environment.set_undefined_behavior(UndefinedBehavior::Strict);
+        // Templates are mostly code, not html
+        environment.set_auto_escape_callback(|_| AutoEscape::None);
+        environment.set_loader(path_loader(&self.root));
+        // Booleans are written as they were answered, not as Python's True
+        environment.set_formatter(|out, state, value| match value.kind() {
+            ValueKind::Bool => write!(out, "{}", value.is_true()).map_err(Into::into),
+            _ => escape_formatter(out, state, value),
+        });
+
+        // Keep files as they were, including their last newline
+        if let Ok(syntax) = SyntaxConfig::builder().keep_trailing_newline(true).build() {
+            environment.set_syntax(syntax);
+        }
+
+        environment.add_filter("apply", |value: String, functions: String| {
+            Apply::parse(&functions)
+                .map(|a| a.execute(value))
+                .map_err(|e| {
+                    minijinja::Error::new(minijinja::ErrorKind::InvalidOperation, e.to_string())
+                })
+        });
+
+        environment
+    }
+
+    /// Rendered contents of each file
+    fn render(&self, global: &Globals) -> Result<Vec<(PathBuf, PathBuf, String)>> {
+        let environment = self.environment();
+        let context = context(global);
+
+        self.files()?
+            .into_iter()
+            .map(|(source, target)| {
+                let name = source.strip_prefix(&self.root).unwrap_or(&source);
+                let invalid = |e: String| Error::CantRenderTemplate(name.to_path_buf(), e);
+                let text = fs::read_to_string(&source).map_err(|e| invalid(e.to_string()))?;
+
+                let rendered = environment
+                    .render_named_str(&name.to_string_lossy(), &text, context.clone())
+                    .map_err(|e| invalid(e.to_string()))?;
+
+                Ok((source, target, rendered))
+            })
+            .collect()
+    }
+
+    pub fn execute(&self, global: &mut Globals) -> Result<()> {
+        for (source, target, contents) in self.render(global)? {
+            fs::write(&target, contents)?;
+
+            if source != target {
+                fs::remove_file(&source)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Files which execution would render
+    pub fn plan(&self, global: &Globals, root: &Path) -> Result<String> {
+        let relative = |p: &Path| p.strip_prefix(root).unwrap_or(p).display().to_string();
+
+        Ok(self
+            .render(global)?
+            .iter()
+            .map(|(source, target, _)| match source == target {
+                true => format!("{} {}", "render".magenta(), relative(source)),
+                false => format!(
+                    "{} {} -> {}",
+                    "render".magenta(),
+                    relative(source),
+                    relative(target)
+                ),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+}
+
+/// Globals as template context, where dotted names like
+/// `git.user.name` become nested objects, choices of
+/// multiselect are lists to loop over and yes/no answers
+/// are booleans, so `{% if docker %}` works as expected
+fn context(global: &Globals) -> Value {
+    let entries = global
+        .iter()
+        .map(|(k, v)| (k.split('.').collect::<Vec<_>>(), v))
+        .collect::<Vec<_>>();
+
+    nest(&entries)
+}
+
+fn nest(entries: &[(Vec<&str>, &value::Value)]) -> Value {
+    let mut groups: BTreeMap<&str, Vec<(Vec<&str>, &value::Value)>> = BTreeMap::new();
+
+    for (path, value) in entries {
+        if let Some((first, rest)) = path.split_first() {
+            groups
+                .entry(first)
+                .or_default()
+                .push((rest.to_vec(), value));
+        }
+    }
+
+    Value::from_pairs(groups.into_iter().map(|(name, children)| {
+        // Plain variable wins over dotted ones sharing its name
+        match children.iter().find(|(rest, _)| rest.is_empty()) {
+            Some((_, value::Value::List(items))) => (name, Value::from(items.to_owned())),
+            Some((_, value::Value::Bool(b))) => (name, Value::from(*b)),
+            Some((_, value)) => (name, Value::from(value.to_string())),
+            None => (name, nest(&children)),
+        }
+    }))
+}
+
+impl ToTask for Render {
+    fn to_task(self, path: &Path) -> Task {
+        Task::Render(Render {
+            root: path.to_path_buf(),
+            patterns: self.patterns,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passes_choices_as_lists() {
+        let global = Globals::from([
+            (
+                "features".to_owned(),
+                value::Value::List(vec!["a,b".into(), "c".into()]),
+            ),
+            ("git.user.name".to_owned(), "me".into()),
+        ]);
+
+        let rendered = Render::new(Vec::new())
+            .environment()
+            .render_str(
+                "{% for f in features %}[{{ f }}]{% endfor %} {{ git.user.name }}",
+                context(&global),
+            )
+            .unwrap();
+
+        assert_eq!(rendered, "[a,b][c] me");
+    }
+
+    #[test]
+    fn passes_answers_as_booleans() {
+        let global = Globals::from([
+            ("docker".to_owned(), value::Value::Bool(false)),
+            ("ci".to_owned(), value::Value::Bool(true)),
+        ]);
+
+        let rendered = Render::new(Vec::new())
+            .environment()
+            .render_str(
+                "{% if docker %}DOCKER ON{% endif %}{% if ci %}CI {{ ci }}{% endif %}",
+                context(&global),
+            )
+            .unwrap();
+
+        assert_eq!(rendered, "CI true");
+    }
+}
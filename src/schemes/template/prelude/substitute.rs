@@ -0,0 +1,81 @@
+use crate::{
+    execute::task::{Task, ToTask},
+    manager::Glubtastic,
+    schemes::template::prelude::change::{diff, text},
+    value::Globals,
+    Error, Result,
+};
+use owo_colors::OwoColorize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use walkdir::WalkDir;
+
+/// Replaces known @variable@ references in every text file
+#[derive(Debug, Clone, Default, PartialEq, PartialOrd, Eq, Ord)]
+pub struct Substitute {
+    /// Template directory
+    root: PathBuf,
+}
+
+impl Substitute {
+    /// Contents of each file which would change
+    fn render(&self, global: &Globals) -> Result<Vec<(PathBuf, String, String)>> {
+        let mut rendered = Vec::new();
+
+        for entry in WalkDir::new(&self.root).min_depth(1).sort_by_file_name() {
+            let entry = entry.map_err(|e| Error::IOError(e.into()))?;
+
+            // Configuration refers to variables on purpose
+            if !entry.file_type().is_file() || entry.path() == self.root.join("bleur.toml") {
+                continue;
+            }
+
+            let Some(original) = text(entry.path())? else {
+                continue;
+            };
+
+            let contents = global.substitute(&original);
+
+            if contents != original {
+                rendered.push((entry.into_path(), original, contents));
+            }
+        }
+
+        Ok(rendered)
+    }
+
+    pub fn execute(&self, global: &mut Globals) -> Result<()> {
+        for (file, _, contents) in self.render(global)? {
+            fs::write(file, contents)?;
+        }
+
+        Ok(())
+    }
+
+    /// Unified diffs of what execution would do
+    pub fn plan(&self, global: &Globals, root: &Path) -> Result<String> {
+        Ok(self
+            .render(global)?
+            .iter()
+            .map(|(file, original, contents)| {
+                diff(
+                    "change".yellow(),
+                    file.strip_prefix(root).unwrap_or(file),
+                    original,
+                    contents,
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+}
+
+impl ToTask for Substitute {
+    fn to_task(self, path: &Path) -> Task {
+        Task::Substitute(Substitute {
+            root: path.to_path_buf(),
+        })
+    }
+}
@@ -1,20 +1,70 @@
 use crate::{
     execute::task::{Task, ToTask},
+    manager::Glubtastic,
+    schemes::template::apply::Apply,
+    value::{Globals, Value},
     Error, Result,
 };
+use owo_colors::OwoColorize;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, path::Path};
+use std::path::Path;
+
+/// Kind of value variable holds, decides which prompt is shown
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, PartialOrd, Eq, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    /// Free text
+    #[default]
+    String,
+
+    /// Yes or no, stored as "true" or "false"
+    Bool,
+
+    /// One of options
+    Select,
+
+    /// Any of options, stored as a list in order of options
+    Multiselect,
+
+    /// Whole number, optionally within min and max
+    Integer,
+
+    /// Free text which is never shown back
+    Secret,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, PartialOrd, Eq, Ord)]
 pub struct Variable {
     /// Variable to make use of in names of files while moving
     variable: String,
 
-    /// Default value to be picked up
-    default: String,
+    /// Kind of value and prompt for it
+    #[serde(default, rename = "type")]
+    kind: Kind,
+
+    /// Choices for select and multiselect
+    #[serde(default)]
+    options: Vec<String>,
+
+    /// Lower bound for integer
+    min: Option<i64>,
+
+    /// Upper bound for integer
+    max: Option<i64>,
+
+    /// Default value to be picked up, might refer to earlier variables
+    default: Option<String>,
+
+    /// Computed value from earlier variables, never asked if given
+    value: Option<String>,
+
+    /// Functions to apply on value
+    #[serde(default)]
+    apply: String,
 
     /// Question to ask from user to get value
+    #[serde(default)]
     message: String,
 
     /// Regex for validating user input
@@ -22,39 +72,326 @@ pub struct Variable {
 
     /// Input validation feedback for user
     pattern_error: Option<String>,
+
+    /// Run only if this expression holds
+    #[serde(default)]
+    when: Option<String>,
 }
 
 impl Variable {
-    pub fn execute(&self, global: &mut HashMap<String, String>) -> Result<()> {
-        let mut inquire_prompt = inquire::Text::new(&self.message)
-            .with_default(&self.default)
-            .with_placeholder(&self.default);
-
-        if let Some(pattern) = self.pattern.as_ref() {
-            let pattern = Regex::new(pattern).expect("Failed to parse pattern");
-            let error_message = self.pattern_error.as_ref();
-
-            inquire_prompt = inquire_prompt.with_validator(move |input: &str| {
-                if pattern.is_match(input) {
-                    Ok(inquire::validator::Validation::Valid)
-                } else {
-                    Ok(inquire::validator::Validation::Invalid(
-                        error_message
-                            .map(Into::into)
-                            .unwrap_or(inquire::validator::ErrorMessage::Default),
-                    ))
+    pub fn when(&self) -> Option<&String> {
+        self.when.as_ref()
+    }
+
+    pub fn apply(&self) -> &str {
+        &self.apply
+    }
+
+    pub fn name(&self) -> &str {
+        &self.variable
+    }
+
+    /// Texts which might refer to other variables
+    pub fn references(&self) -> impl Iterator<Item = &String> {
+        [&self.value, &self.default, &self.when]
+            .into_iter()
+            .flatten()
+    }
+
+    pub fn execute(&self, global: &mut Globals, interactive: bool) -> Result<()> {
+        self.check()?;
+
+        let pattern = self
+            .pattern
+            .as_ref()
+            .map(|p| Regex::new(p))
+            .transpose()
+            .map_err(Error::InvalidRegex)?;
+
+        let default = || {
+            self.default
+                .as_ref()
+                .map(|d| global.interpolate(d))
+                .transpose()
+        };
+
+        // Value was already given via --var or answers file. Only those
+        // and computed ones are checked against pattern here, as prompts
+        // check input on their own and default comes from template itself.
+        let (value, given) = match (global.get(&self.variable), &self.value) {
+            (Some(value), _) => (value.to_owned(), true),
+            (None, Some(value)) => (global.interpolate(value)?.into(), true),
+            (None, None) if !interactive => (
+                default()?
+                    .ok_or(Error::MissingVariable(self.variable.clone()))?
+                    .into(),
+                false,
+            ),
+            (None, None) => (self.prompt(pattern.clone(), default()?)?, false),
+        };
+
+        let applications = Apply::parse(&self.apply)?;
+        let value = self.normalize(value.map(|v| applications.execute(v)))?;
+
+        if given {
+            self.validate(pattern.as_ref(), &value)?;
+        }
+
+        global.insert(self.variable.clone(), value);
+
+        Ok(())
+    }
+
+    /// Make sure definition in bleur.toml makes sense for its kind
+    fn check(&self) -> Result<()> {
+        let invalid = |reason: &str| {
+            Err(Error::InvalidVariableDefinition(
+                self.variable.clone(),
+                reason.to_owned(),
+            ))
+        };
+
+        match self.kind {
+            Kind::Select | Kind::Multiselect if self.options.is_empty() => {
+                invalid("options are required for select and multiselect")
+            }
+            Kind::Integer if self.min.zip(self.max).is_some_and(|(l, h)| l > h) => {
+                invalid("min is greater than max")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Bring value to the form stored in globals, refusing
+    /// anything which doesn't fit kind of variable
+    fn normalize(&self, value: Value) -> Result<Value> {
+        let text = value.to_string();
+        let invalid = |reason: String| {
+            Error::InvalidVariableValue(self.variable.clone(), text.clone(), reason)
+        };
+
+        match self.kind {
+            Kind::String => Ok(Value::Text(text)),
+            Kind::Secret => Ok(Value::Secret(text)),
+            Kind::Bool => match text.trim().to_lowercase().as_str() {
+                "true" | "yes" | "y" | "1" | "on" => Ok(Value::Bool(true)),
+                "false" | "no" | "n" | "0" | "off" => Ok(Value::Bool(false)),
+                _ => Err(invalid("expected true or false".into())),
+            },
+            Kind::Select => self
+                .options
+                .iter()
+                .find(|o| *o == text.trim())
+                .map(|o| o.as_str().into())
+                .ok_or_else(|| invalid(format!("expected one of: {}", self.options.join(", ")))),
+            Kind::Multiselect => {
+                // Lists of answers file are taken as they are, text is comma separated
+                let chosen = value.items();
+
+                if let Some(c) = chosen.iter().find(|c| !self.options.contains(c)) {
+                    return Err(invalid(format!(
+                        "{c} is not one of: {}",
+                        self.options.join(", ")
+                    )));
                 }
-            });
+
+                Ok(Value::List(
+                    self.options
+                        .iter()
+                        .filter(|o| chosen.contains(o))
+                        .cloned()
+                        .collect(),
+                ))
+            }
+            Kind::Integer => {
+                let number = text
+                    .trim()
+                    .parse::<i64>()
+                    .map_err(|_| invalid("expected a whole number".into()))?;
+
+                bounds(self.min, self.max, number).map_err(invalid)?;
+
+                Ok(number.to_string().into())
+            }
+        }
+    }
+
+    /// Ask user for value with prompt matching kind of variable
+    fn prompt(&self, pattern: Option<Regex>, default: Option<String>) -> Result<Value> {
+        let default = default.map(|d| self.normalize(d.into())).transpose()?;
+        let text = default.as_ref().map(|d| d.to_string());
+
+        match self.kind {
+            Kind::String => self.text(pattern, text).map(Value::Text),
+            Kind::Secret => {
+                let mut prompt = inquire::Password::new(&self.message)
+                    .without_confirmation()
+                    .with_display_mode(inquire::PasswordDisplayMode::Masked);
+
+                if let Some(pattern) = pattern {
+                    prompt = prompt.with_validator(self.validator(pattern, text.clone()));
+                }
+
+                // Empty input falls back to default, there's no other way to show it
+                prompt
+                    .prompt()
+                    .map(|s| match text {
+                        Some(d) if s.is_empty() => Value::Secret(d),
+                        _ => Value::Secret(s),
+                    })
+                    .map_err(Error::CantParseUserPrompt)
+            }
+            Kind::Bool => {
+                let mut prompt = inquire::Confirm::new(&self.message);
+
+                if let Some(default) = text {
+                    prompt = prompt.with_default(default == "true");
+                }
+
+                prompt
+                    .prompt()
+                    .map(Value::Bool)
+                    .map_err(Error::CantParseUserPrompt)
+            }
+            Kind::Select => {
+                let cursor = text
+                    .and_then(|d| self.options.iter().position(|o| *o == d))
+                    .unwrap_or_default();
+
+                inquire::Select::new(&self.message, self.options.clone())
+                    .with_starting_cursor(cursor)
+                    .prompt()
+                    .map(Value::Text)
+                    .map_err(Error::CantParseUserPrompt)
+            }
+            Kind::Multiselect => {
+                let chosen = default.map(|d| d.items()).unwrap_or_default();
+                let selected = self
+                    .options
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, o)| chosen.contains(o))
+                    .map(|(i, _)| i)
+                    .collect::<Vec<_>>();
+
+                inquire::MultiSelect::new(&self.message, self.options.clone())
+                    .with_default(&selected)
+                    .prompt()
+                    .map(Value::List)
+                    .map_err(Error::CantParseUserPrompt)
+            }
+            Kind::Integer => {
+                let (min, max) = (self.min, self.max);
+                let mut prompt = inquire::CustomType::<i64>::new(&self.message)
+                    .with_error_message("Please type a whole number")
+                    .with_validator(move |n: &i64| {
+                        Ok(match bounds(min, max, *n) {
+                            Ok(()) => inquire::validator::Validation::Valid,
+                            Err(e) => inquire::validator::Validation::Invalid(e.into()),
+                        })
+                    });
+
+                if let Some(default) = text.and_then(|d| d.parse().ok()) {
+                    prompt = prompt.with_default(default);
+                }
+
+                prompt
+                    .prompt()
+                    .map(|n| n.to_string().into())
+                    .map_err(Error::CantParseUserPrompt)
+            }
+        }
+    }
+
+    fn text(&self, pattern: Option<Regex>, default: Option<String>) -> Result<String> {
+        let mut inquire_prompt = inquire::Text::new(&self.message);
+
+        if let Some(default) = default.as_deref() {
+            inquire_prompt = inquire_prompt
+                .with_default(default)
+                .with_placeholder(default);
+        }
+
+        if let Some(pattern) = pattern {
+            inquire_prompt =
+                inquire_prompt.with_validator(self.validator(pattern, default.clone()));
         }
 
-        inquire_prompt
-            .prompt()
-            .map_err(Error::CantParseUserPrompt)
-            .map(|s| {
-                global
-                    .insert(self.variable.clone(), s)
-                    .map_or_else(|| (), |_| ())
+        inquire_prompt.prompt().map_err(Error::CantParseUserPrompt)
+    }
+
+    /// Validator of free text input against pattern, where accepting
+    /// default is fine either way, as it comes from template itself
+    fn validator(
+        &self,
+        pattern: Regex,
+        default: Option<String>,
+    ) -> impl inquire::validator::StringValidator {
+        let error_message = self.pattern_error.clone();
+
+        move |input: &str| {
+            let fallback = default
+                .as_deref()
+                .is_some_and(|d| input.is_empty() || input == d);
+
+            if fallback || pattern.is_match(input) {
+                Ok(inquire::validator::Validation::Valid)
+            } else {
+                Ok(inquire::validator::Validation::Invalid(
+                    error_message
+                        .clone()
+                        .map(Into::into)
+                        .unwrap_or(inquire::validator::ErrorMessage::Default),
+                ))
+            }
+        }
+    }
+
+    /// Resolved value of variable, once it got executed
+    pub fn plan(&self, global: &Globals) -> Result<String> {
+        global
+            .get(&self.variable)
+            .map(|v| match (self.kind, v) {
+                (Kind::Secret, _) => format!(
+                    "{} {} = {}",
+                    "variable".green(),
+                    self.variable,
+                    "***".dimmed()
+                ),
+                (_, Value::List(items)) => {
+                    format!("{} {} = {:?}", "variable".green(), self.variable, items)
+                }
+                _ => format!(
+                    "{} {} = {:?}",
+                    "variable".green(),
+                    self.variable,
+                    v.to_string()
+                ),
             })
+            .ok_or(Error::NoSuchVariable(self.variable.clone()))
+    }
+
+    /// Check non-prompted value against pattern
+    fn validate(&self, pattern: Option<&Regex>, value: &Value) -> Result<()> {
+        match pattern {
+            Some(p) if !p.is_match(&value.to_string()) => Err(Error::InvalidVariableValue(
+                self.variable.clone(),
+                value.redacted().to_string(),
+                self.pattern_error
+                    .clone()
+                    .unwrap_or(format!("doesn't match pattern {}", p.as_str())),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Check integer against min and max
+fn bounds(min: Option<i64>, max: Option<i64>, number: i64) -> std::result::Result<(), String> {
+    match (min, max) {
+        (Some(min), _) if number < min => Err(format!("must be at least {min}")),
+        (_, Some(max)) if number > max => Err(format!("must be at most {max}")),
+        _ => Ok(()),
     }
 }
 
@@ -63,9 +400,16 @@ impl ToTask for Variable {
         Task::Variable(Variable {
             message: self.message,
             default: self.default,
+            value: self.value,
+            apply: self.apply,
             variable: self.variable,
+            kind: self.kind,
+            options: self.options,
+            min: self.min,
+            max: self.max,
             pattern: self.pattern,
             pattern_error: self.pattern_error,
+            when: self.when,
         })
     }
 }
@@ -0,0 +1,84 @@
+use std::{collections::HashMap, fmt};
+
+/// Global variables, built-in ones along with those of template
+pub type Globals = HashMap<String, Value>;
+
+/// Value of a global variable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    /// Plain text, which is what most variables hold
+    Text(String),
+
+    /// Choices of multiselect, kept apart as options may contain commas
+    List(Vec<String>),
+
+    /// Text which must never be shown back to user
+    Secret(String),
+
+    /// Answer of yes/no question, so templates can branch on it
+    Bool(bool),
+}
+
+impl Value {
+    /// Items of list, where comma separated text counts as one too
+    pub fn items(&self) -> Vec<String> {
+        match self {
+            Self::List(items) => items.to_owned(),
+            Self::Text(text) | Self::Secret(text) => text
+                .split(',')
+                .map(|i| i.trim().to_owned())
+                .filter(|i| !i.is_empty())
+                .collect(),
+            Self::Bool(b) => vec![b.to_string()],
+        }
+    }
+
+    /// Same kind of value with function applied to its text
+    pub fn map(self, f: impl Fn(String) -> String) -> Self {
+        match self {
+            Self::Text(text) => Self::Text(f(text)),
+            Self::List(items) => Self::List(items.into_iter().map(f).collect()),
+            Self::Secret(text) => Self::Secret(f(text)),
+            Self::Bool(b) => Self::Text(f(b.to_string())),
+        }
+    }
+
+    /// Value fit for printing, with secrets masked out
+    pub fn redacted(&self) -> Self {
+        match self {
+            Self::Secret(_) => Self::Text("***".to_owned()),
+            other => other.to_owned(),
+        }
+    }
+}
+
+/// Text which @variable@ references are replaced with
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text(text) | Self::Secret(text) => write!(f, "{text}"),
+            Self::List(items) => write!(f, "{}", items.join(",")),
+            Self::Bool(b) => write!(f, "{b}"),
+        }
+    }
+}
+
+impl From<String> for Value {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_owned())
+    }
+}
+
+/// Globals with secrets masked, for plans shown to user
+pub fn redacted(global: &Globals) -> Globals {
+    global
+        .iter()
+        .map(|(k, v)| (k.to_owned(), v.redacted()))
+        .collect()
+}